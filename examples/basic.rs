@@ -21,8 +21,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .bind("Alice")
                 .bind("alice@example.com")
                 .execute(tx.as_executor())
-                .await
-                .map_err(|e| e.into())?;
+                .await?;
             Ok(())
         })
     })
@@ -38,8 +37,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .bind("Bob")
                 .bind("bob@example.com")
                 .execute(tx.as_executor())
-                .await
-                .map_err(|e| e.into())?;
+                .await?;
 
             let user_id = result.last_insert_id() as i64;
 
@@ -48,8 +46,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .bind(user_id)
                 .bind("Software Developer")
                 .execute(tx.as_executor())
-                .await
-                .map_err(|e| e.into())?;
+                .await?;
 
             // Both operations commit together
             Ok(user_id)
@@ -66,14 +63,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .bind("Charlie")
                 .bind("charlie@example.com")
                 .execute(tx.as_executor())
-                .await
-                .map_err(|e| e.into())?;
+                .await?;
 
             // This will cause an error
             sqlx::query("SELECT * FROM non_existent_table")
                 .execute(tx.as_executor())
-                .await
-                .map_err(|e| e.into())?;
+                .await?;
 
             Ok(())
         })
@@ -91,13 +86,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Box::pin(async move {
             let users: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM users")
                 .fetch_one(tx.as_executor())
-                .await
-                .map_err(|e| e.into())?;
+                .await?;
 
             let profiles: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM profiles")
                 .fetch_one(tx.as_executor())
-                .await
-                .map_err(|e| e.into())?;
+                .await?;
 
             Ok((users.0, profiles.0))
         })
@@ -22,8 +22,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .bind("David")
                 .bind("david@example.com")
                 .execute(tx.as_executor())
-                .await
-                .map_err(|e| e.into())?;
+                .await?;
 
             let user_id = result.last_insert_id() as i64;
             println!("   Outer: Created user with ID {}", user_id);
@@ -35,8 +34,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                         .bind(user_id)
                         .bind("User created")
                         .execute(nested_tx.as_executor())
-                        .await
-                        .map_err(|e| e.into())?;
+                        .await?;
                     println!("   Nested: Created audit log");
                     Ok(())
                 })
@@ -58,8 +56,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .bind("Eve")
                 .bind("eve@example.com")
                 .execute(tx.as_executor())
-                .await
-                .map_err(|e| e.into())?;
+                .await?;
 
             let user_id = result.last_insert_id() as i64;
             println!("   Outer: Created user with ID {}", user_id);
@@ -71,8 +68,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     sqlx::query("INSERT INTO non_existent_table VALUES (?)")
                         .bind(user_id)
                         .execute(nested_tx.as_executor())
-                        .await
-                        .map_err(|e| e.into())?;
+                        .await?;
                     Ok(())
                 })
             })
@@ -98,8 +94,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .bind("Frank")
                 .bind("frank@example.com")
                 .execute(tx.as_executor())
-                .await
-                .map_err(|e| e.into())?;
+                .await?;
 
             let user_id = result.last_insert_id() as i64;
             println!("   Outer: Created user with ID {}", user_id);
@@ -111,8 +106,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                         .bind(user_id)
                         .bind("Data Scientist")
                         .execute(nested_tx1.as_executor())
-                        .await
-                        .map_err(|e| e.into())?;
+                        .await?;
                     println!("   Nested 1: Created profile");
                     Ok(())
                 })
@@ -127,8 +121,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                         .bind(user_id)
                         .bind("Profile created")
                         .execute(nested_tx2.as_executor())
-                        .await
-                        .map_err(|e| e.into())?;
+                        .await?;
                     println!("   Nested 2: Created audit log");
                     Ok(())
                 })
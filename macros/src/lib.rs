@@ -0,0 +1,270 @@
+//! Procedural macros for [sqlx-transaction-manager](https://docs.rs/sqlx-transaction-manager).
+//!
+//! Use them through the `macros` feature of `sqlx-transaction-manager`, which re-exports
+//! them; this crate is not meant to be depended on directly.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{quote, quote_spanned};
+use syn::parse::Parser;
+use syn::{Expr, Ident, ItemFn, LitInt, LitStr, ReturnType};
+
+/// Runs the body of an async fn inside a transaction.
+///
+/// The first argument names the `&mut TransactionContext<'_>` binding the body uses, as
+/// in `#[transactional(tx)]`. The transaction is committed when the body returns `Ok`,
+/// and rolled back when it returns `Err`. The function must return a `Result` whose error
+/// type implements `From<sqlx_transaction_manager::Error>` and is `'static`, so failures to
+/// begin or commit can be returned.
+///
+/// When the error type is `sqlx_transaction_manager::Error`, errors from the body are
+/// handled like in `with_transaction_async`: they carry the transaction's label, journal
+/// and deadlock report. Other error types are returned as is.
+///
+/// # Arguments
+///
+/// - `<name>`: the name of the transaction binding (required, first).
+/// - `pool = <expr>`: the `MySqlPool` to begin the transaction on (default: a `pool`
+///   parameter). Both `MySqlPool` and `&MySqlPool` expressions are accepted.
+/// - `options = <expr>`: base `TransactionOptions` the other arguments are applied to.
+/// - `isolation = <level>`: an `IsolationLevel` variant, such as `ReadCommitted`.
+/// - `read_only`: start the transaction with `START TRANSACTION READ ONLY`.
+/// - `retry = <n>`: run up to `n` times on deadlocks and lock wait timeouts. The error
+///   type must implement `RetryableError`, and because the body may run several times it
+///   must not consume the function's arguments.
+/// - `label = "<name>"`: label the transaction.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use sqlx_transaction_manager::{transactional, Result};
+///
+/// impl OrderService {
+///     #[transactional(tx, pool = self.pool, isolation = ReadCommitted, retry = 3, label = "checkout.place_order")]
+///     async fn place_order(&self, user_id: i64) -> Result<u64> {
+///         let result = sqlx::query("INSERT INTO orders (user_id) VALUES (?)")
+///             .bind(user_id)
+///             .execute(&mut *tx)
+///             .await?;
+///         Ok(result.last_insert_id())
+///     }
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn transactional(args: TokenStream, item: TokenStream) -> TokenStream {
+    let mut config = Config::default();
+    let parser = syn::meta::parser(|meta| config.parse(meta));
+    if let Err(e) = parser.parse(args) {
+        return e.to_compile_error().into();
+    }
+
+    match syn::parse::<ItemFn>(item).and_then(|item| expand(config, item)) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+#[derive(Default)]
+struct Config {
+    tx: Option<Ident>,
+    pool: Option<Expr>,
+    options: Option<Expr>,
+    isolation: Option<Ident>,
+    read_only: bool,
+    retry: Option<LitInt>,
+    label: Option<LitStr>,
+}
+
+impl Config {
+    fn parse(&mut self, meta: syn::meta::ParseNestedMeta<'_>) -> syn::Result<()> {
+        if self.tx.is_none() {
+            return match meta.path.get_ident() {
+                Some(tx) if meta.input.is_empty() || meta.input.peek(syn::Token![,]) => {
+                    self.tx = Some(tx.clone());
+                    Ok(())
+                }
+                _ => Err(meta.error(
+                    "expected the name of the transaction binding first, as in \
+                     `#[transactional(tx)]`",
+                )),
+            };
+        }
+
+        if meta.path.is_ident("pool") {
+            self.pool = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("options") {
+            self.options = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("isolation") {
+            self.isolation = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("read_only") {
+            self.read_only = true;
+        } else if meta.path.is_ident("retry") {
+            let retry: LitInt = meta.value()?.parse()?;
+            retry.base10_parse::<u32>()?;
+            self.retry = Some(retry);
+        } else if meta.path.is_ident("label") {
+            self.label = Some(meta.value()?.parse()?);
+        } else {
+            return Err(meta.error(
+                "unsupported argument; expected one of `pool`, `options`, `isolation`, \
+                 `read_only`, `retry` or `label`",
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn expand(config: Config, item: ItemFn) -> syn::Result<TokenStream2> {
+    let ItemFn {
+        attrs,
+        vis,
+        sig,
+        block,
+    } = item;
+
+    if sig.asyncness.is_none() {
+        return Err(syn::Error::new_spanned(
+            sig.fn_token,
+            "#[transactional] can only be applied to async functions",
+        ));
+    }
+    let output = match &sig.output {
+        ReturnType::Type(_, ty) => ty,
+        ReturnType::Default => {
+            return Err(syn::Error::new_spanned(
+                &sig,
+                "#[transactional] functions must return a `Result`",
+            ))
+        }
+    };
+
+    let krate = quote!(::sqlx_transaction_manager);
+    let tx = config.tx.ok_or_else(|| {
+        syn::Error::new(
+            Span::call_site(),
+            "expected the name of the transaction binding, as in `#[transactional(tx)]`",
+        )
+    })?;
+    let pool = config.pool.unwrap_or_else(|| syn::parse_quote!(pool));
+    let mut options = match config.options {
+        Some(options) => quote!(#options),
+        None => quote!(#krate::TransactionOptions::new()),
+    };
+    if let Some(level) = config.isolation {
+        options = quote!(#options.isolation_level(#krate::IsolationLevel::#level));
+    }
+    if config.read_only {
+        options = quote!(#options.read_only());
+    }
+    if let Some(label) = config.label {
+        options = quote!(#options.label(#label));
+    }
+
+    // Locals are resolved at the macro definition site so the body cannot see or clash
+    // with them; the binding keeps the caller's span so the body can use it.
+    let span = Span::mixed_site();
+    let retry_check = match &config.retry {
+        Some(retry) => quote_spanned! {span=>
+            if #krate::RetryPolicy::attempts(#retry).should_retry_error(attempt, &error) {
+                continue;
+            }
+        },
+        None => quote!(),
+    };
+
+    Ok(quote_spanned! {span=>
+        #(#attrs)*
+        #vis #sig {
+            let pool: &#krate::__private::MySqlPool = &#pool;
+            let options: #krate::TransactionOptions = #options;
+            let mut attempt: u32 = 0;
+            loop {
+                attempt += 1;
+                let result: #output = match #krate::TransactionContext::begin_with(
+                    pool,
+                    ::core::clone::Clone::clone(&options),
+                )
+                .await
+                {
+                    ::core::result::Result::Ok(mut context) => {
+                        let result: #output = {
+                            let #tx: &mut #krate::TransactionContext<'_> = &mut context;
+                            #krate::__private::output::<#output, _>(async #block).await
+                        };
+                        #krate::__private::finish(context, result).await
+                    }
+                    ::core::result::Result::Err(error) => {
+                        ::core::result::Result::Err(::core::convert::From::from(error))
+                    }
+                };
+
+                match result {
+                    ::core::result::Result::Err(error) => {
+                        #retry_check
+                        return ::core::result::Result::Err(error);
+                    }
+                    result => return result,
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(args: TokenStream2) -> syn::Result<Config> {
+        let mut config = Config::default();
+        syn::meta::parser(|meta| config.parse(meta)).parse2(args)?;
+        Ok(config)
+    }
+
+    #[test]
+    fn test_parses_all_arguments() {
+        let config = config(quote!(
+            tx,
+            pool = self.pool,
+            isolation = ReadCommitted,
+            read_only,
+            retry = 3,
+            label = "checkout.place_order"
+        ))
+        .unwrap();
+        assert_eq!(config.tx.unwrap(), "tx");
+        assert!(config.pool.is_some());
+        assert_eq!(config.isolation.unwrap(), "ReadCommitted");
+        assert!(config.read_only);
+        assert_eq!(config.retry.unwrap().base10_parse::<u32>().unwrap(), 3);
+        assert_eq!(config.label.unwrap().value(), "checkout.place_order");
+    }
+
+    #[test]
+    fn test_rejects_unknown_arguments() {
+        assert!(config(quote!(tx, timeout = 5)).is_err());
+        assert!(config(quote!(tx, retry = "3")).is_err());
+    }
+
+    #[test]
+    fn test_requires_the_binding_name_first() {
+        assert!(config(quote!(pool = self.pool)).is_err());
+        assert!(config(quote!(pool = self.pool, tx)).is_err());
+
+        let item: ItemFn = syn::parse_quote!(
+            async fn f(pool: &MySqlPool) -> Result<()> {
+                Ok(())
+            }
+        );
+        assert!(expand(Config::default(), item).is_err());
+    }
+
+    #[test]
+    fn test_rejects_non_async_functions() {
+        let item: ItemFn = syn::parse_quote!(
+            fn f(pool: &MySqlPool) -> Result<()> {
+                Ok(())
+            }
+        );
+        assert!(expand(config(quote!(tx)).unwrap(), item).is_err());
+    }
+}
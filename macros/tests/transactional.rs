@@ -0,0 +1,118 @@
+use sqlx::MySqlPool;
+use sqlx_transaction_manager::{transactional, Error, Result, RetryableError};
+
+struct OrderService {
+    pool: MySqlPool,
+}
+
+impl OrderService {
+    #[transactional(tx, pool = self.pool, isolation = ReadCommitted, retry = 3, label = "checkout.place_order")]
+    async fn place_order(&self, user_id: i64) -> Result<u64> {
+        let result = sqlx::query("INSERT INTO orders (user_id) VALUES (?)")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        Ok(result.last_insert_id())
+    }
+
+    #[transactional(tx, pool = &self.pool, read_only)]
+    async fn count_orders(&self) -> Result<i64> {
+        let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM orders")
+            .fetch_one(&mut *tx)
+            .await?;
+        if count < 0 {
+            return Err(Error::Other("negative count".into()));
+        }
+        Ok(count)
+    }
+}
+
+#[derive(Debug)]
+enum AppError {
+    Database(Error),
+}
+
+impl From<Error> for AppError {
+    fn from(e: Error) -> Self {
+        AppError::Database(e)
+    }
+}
+
+impl RetryableError for AppError {
+    fn mysql_error_number(&self) -> Option<u16> {
+        match self {
+            AppError::Database(e) => e.mysql_error_number(),
+        }
+    }
+}
+
+impl From<sqlx::Error> for AppError {
+    fn from(e: sqlx::Error) -> Self {
+        AppError::Database(e.into())
+    }
+}
+
+#[transactional(conn, retry = 2)]
+async fn with_app_error(pool: &MySqlPool, name: String) -> std::result::Result<usize, AppError> {
+    assert!(conn.is_active());
+    sqlx::query("INSERT INTO users (name) VALUES (?)")
+        .bind(name.as_str())
+        .execute(&mut *conn)
+        .await?;
+    Ok(name.len())
+}
+
+#[transactional(tx, label = "tests.insert_name")]
+async fn insert_name(pool: &MySqlPool, name: &str, fail: bool) -> Result<()> {
+    sqlx::query("INSERT INTO transactional_names (name) VALUES (?)")
+        .bind(name)
+        .execute(&mut *tx)
+        .await?;
+    if fail {
+        return Err(Error::Other("failed after insert".into()));
+    }
+    Ok(())
+}
+
+fn assert_send<T: Send>(_: &T) {}
+
+#[tokio::test]
+async fn test_transactional_functions_are_send() {
+    let pool = MySqlPool::connect_lazy("mysql://localhost/test").unwrap();
+    let service = OrderService { pool: pool.clone() };
+
+    assert_send(&service.place_order(1));
+    assert_send(&service.count_orders());
+    assert_send(&with_app_error(&pool, "Alice".into()));
+}
+
+#[tokio::test]
+#[ignore = "requires a MySQL server at DATABASE_URL"]
+async fn test_commits_on_ok_and_rolls_back_on_err() {
+    let pool = MySqlPool::connect(&std::env::var("DATABASE_URL").unwrap())
+        .await
+        .unwrap();
+    // A regular table, since the pool may run each step on a different connection.
+    sqlx::query("DROP TABLE IF EXISTS transactional_names")
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query("CREATE TABLE transactional_names (name VARCHAR(64) NOT NULL)")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    insert_name(&pool, "committed", false).await.unwrap();
+    let error = insert_name(&pool, "rolled_back", true).await.unwrap_err();
+    assert_eq!(error.label(), Some("tests.insert_name"));
+
+    let names: Vec<(String,)> = sqlx::query_as("SELECT name FROM transactional_names")
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+    sqlx::query("DROP TABLE transactional_names")
+        .execute(&pool)
+        .await
+        .unwrap();
+    assert_eq!(names, vec![("committed".to_owned(),)]);
+}
@@ -0,0 +1,213 @@
+//! Per-request transactions for [actix-web](https://docs.rs/actix-web).
+//!
+//! Requires the `actix` feature. [`TransactionMiddleware`] begins a transaction for every
+//! request, and handlers reach it through the [`Tx`] extractor. The transaction is
+//! committed when the response status is 2xx, and rolled back on other responses and on
+//! errors, unless the handler decides with [`Tx::set_commit`].
+//!
+//! actix-web runs handlers on a single-threaded runtime, so their futures are usually not
+//! `Send`. The middleware and extractor do not require it. To run a transaction inside a
+//! handler without the middleware, use
+//! [`with_transaction_async`](crate::with_transaction_async), which does not require
+//! `Send` either.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use actix_web::{web, App, HttpResponse};
+//! use sqlx::MySqlPool;
+//! use sqlx_transaction_manager::actix::{TransactionMiddleware, Tx};
+//!
+//! async fn create_user(mut tx: Tx) -> actix_web::Result<HttpResponse> {
+//!     let result = sqlx::query("INSERT INTO users (name) VALUES (?)")
+//!         .bind("Alice")
+//!         .execute(&mut *tx)
+//!         .await
+//!         .map_err(actix_web::error::ErrorInternalServerError)?;
+//!     Ok(HttpResponse::Created().body(result.last_insert_id().to_string()))
+//! }
+//!
+//! # fn example(pool: MySqlPool) {
+//! let app = App::new()
+//!     .wrap(TransactionMiddleware::new(pool))
+//!     .route("/users", web::post().to(create_user));
+//! # }
+//! ```
+
+use crate::context::TransactionContext;
+use crate::options::TransactionOptions;
+use crate::request::{RequestTransaction, RequestTransactionGuard};
+use actix_web::body::MessageBody;
+use actix_web::dev::{forward_ready, Payload, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::error::{ErrorInternalServerError, ErrorServiceUnavailable};
+use actix_web::{FromRequest, HttpMessage, HttpRequest};
+use sqlx::MySqlPool;
+use std::future::{ready, Future, Ready};
+use std::pin::Pin;
+use std::rc::Rc;
+
+/// Middleware that runs every request in a transaction.
+///
+/// After the handler returns, the transaction is committed if the handler asked for it
+/// with [`Tx::set_commit`], or, if it did not decide, when the response status is 2xx.
+/// Otherwise it is rolled back. Work registered with [`Tx::after_commit`] runs once the
+/// commit succeeds, before the response is sent.
+///
+/// Fails the request with `503 Service Unavailable` if the transaction cannot be begun,
+/// and with `500 Internal Server Error` if the commit fails.
+#[derive(Debug, Clone)]
+pub struct TransactionMiddleware {
+    pool: MySqlPool,
+    options: TransactionOptions,
+}
+
+impl TransactionMiddleware {
+    /// Creates a middleware beginning transactions on `pool` with default options.
+    pub fn new(pool: MySqlPool) -> Self {
+        Self {
+            pool,
+            options: TransactionOptions::default(),
+        }
+    }
+
+    /// Sets the options every transaction is begun with.
+    pub fn options(mut self, options: TransactionOptions) -> Self {
+        self.options = options;
+        self
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for TransactionMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type Transform = TransactionMiddlewareService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(TransactionMiddlewareService {
+            service: Rc::new(service),
+            pool: self.pool.clone(),
+            options: self.options.clone(),
+        }))
+    }
+}
+
+/// Service created by [`TransactionMiddleware`].
+#[derive(Debug)]
+pub struct TransactionMiddlewareService<S> {
+    service: Rc<S>,
+    pool: MySqlPool,
+    options: TransactionOptions,
+}
+
+impl<S, B> Service<ServiceRequest> for TransactionMiddlewareService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, request: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        let pool = self.pool.clone();
+        let options = self.options.clone();
+
+        Box::pin(async move {
+            let tx = TransactionContext::begin_with(&pool, options)
+                .await
+                .map_err(ErrorServiceUnavailable)?;
+            let tx = RequestTransaction::new(tx);
+            request.extensions_mut().insert(tx.clone());
+
+            match service.call(request).await {
+                Ok(response) => {
+                    tx.finish(response.status().is_success())
+                        .await
+                        .map_err(ErrorInternalServerError)?;
+                    Ok(response)
+                }
+                Err(e) => {
+                    let _ = tx.finish(false).await;
+                    Err(e)
+                }
+            }
+        })
+    }
+}
+
+/// Extractor for the request's transaction, begun by [`TransactionMiddleware`].
+///
+/// Dereferences to the [`TransactionContext`], so `&mut *tx` can be passed to sqlx
+/// queries. A handler can extract it only once; the transaction is finished after the
+/// handler returns. Extraction fails with `500 Internal Server Error` if the middleware
+/// is missing or the transaction has already been extracted.
+pub type Tx = RequestTransactionGuard;
+
+impl FromRequest for RequestTransactionGuard {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(request: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let extensions = request.extensions();
+        let Some(tx) = extensions.get::<RequestTransaction>() else {
+            return ready(Err(ErrorInternalServerError(
+                "no request transaction; is `TransactionMiddleware` installed?",
+            )));
+        };
+        ready(match tx.try_lock() {
+            Some(Ok(tx)) => Ok(tx),
+            Some(Err(_)) | None => Err(ErrorInternalServerError(
+                "the request transaction has already been extracted",
+            )),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::StatusCode;
+    use actix_web::{test, web, App, HttpResponse};
+
+    async fn handler(_tx: Tx) -> HttpResponse {
+        HttpResponse::Ok().finish()
+    }
+
+    #[actix_web::test]
+    async fn test_extractor_requires_middleware() {
+        let app = test::init_service(App::new().route("/", web::get().to(handler))).await;
+        let response =
+            test::call_service(&app, test::TestRequest::get().uri("/").to_request()).await;
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[actix_web::test]
+    async fn test_unavailable_when_begin_fails() {
+        let pool = sqlx::mysql::MySqlPoolOptions::new()
+            .acquire_timeout(std::time::Duration::from_millis(50))
+            .connect_lazy("mysql://localhost:1/test")
+            .unwrap();
+        let app = test::init_service(
+            App::new()
+                .wrap(TransactionMiddleware::new(pool))
+                .route("/", web::get().to(handler)),
+        )
+        .await;
+        let response =
+            test::try_call_service(&app, test::TestRequest::get().uri("/").to_request()).await;
+        let error = response.err().unwrap();
+        assert_eq!(
+            error.as_response_error().status_code(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+    }
+}
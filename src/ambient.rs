@@ -0,0 +1,306 @@
+//! Ambient transactions carried in a tokio task-local.
+//!
+//! Requires the `ambient` feature. [`with_transaction`] begins a transaction and makes it
+//! the current transaction of the task while a future runs, so code deep in the call
+//! stack can reach it with [`current_transaction`] or [`current_or_pool`] instead of
+//! having a `&mut TransactionContext` passed down to it.
+//!
+//! The transaction is only visible to the task running the future. Tasks spawned from
+//! inside the scope do not inherit it.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use sqlx::MySqlPool;
+//! use sqlx_transaction_manager::ambient;
+//!
+//! async fn insert_user(pool: &MySqlPool, name: &str) -> sqlx_transaction_manager::Result<u64> {
+//!     // Runs in the caller's transaction if there is one, or directly on the pool.
+//!     let mut conn = ambient::current_or_pool(pool).await?;
+//!     let result = sqlx::query("INSERT INTO users (name) VALUES (?)")
+//!         .bind(name)
+//!         .execute(&mut conn)
+//!         .await?;
+//!     Ok(result.last_insert_id())
+//! }
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! # let pool = MySqlPool::connect("mysql://localhost/test").await?;
+//! ambient::with_transaction(&pool, async {
+//!     insert_user(&pool, "Alice").await?;
+//!     insert_user(&pool, "Bob").await?;
+//!     Ok(())
+//! })
+//! .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::context::TransactionContext;
+use crate::options::TransactionOptions;
+use futures_core::future::BoxFuture;
+use futures_core::stream::BoxStream;
+use sqlx::mysql::{MySql, MySqlQueryResult, MySqlRow, MySqlStatement, MySqlTypeInfo};
+use sqlx::{Describe, Either, Execute, Executor, MySqlPool};
+use std::future::Future;
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+use tokio::sync::{Mutex, OwnedMutexGuard};
+
+tokio::task_local! {
+    static CURRENT: AmbientTransaction;
+}
+
+/// Runs `future` inside a transaction that it can reach through [`current_transaction`].
+///
+/// The transaction is committed if the future returns `Ok`, and rolled back if it
+/// returns `Err`. If the task is already inside an ambient transaction, the future joins
+/// it instead of beginning a new one, and the outer scope decides whether to commit.
+pub async fn with_transaction<Fut, T>(pool: &MySqlPool, future: Fut) -> crate::Result<T>
+where
+    Fut: Future<Output = crate::Result<T>>,
+{
+    with_transaction_options(pool, TransactionOptions::default(), future).await
+}
+
+/// Runs `future` inside a transaction started with the given options.
+///
+/// Behaves like [`with_transaction`]. The options are ignored when joining an outer
+/// ambient transaction.
+pub async fn with_transaction_options<Fut, T>(
+    pool: &MySqlPool,
+    options: TransactionOptions,
+    future: Fut,
+) -> crate::Result<T>
+where
+    Fut: Future<Output = crate::Result<T>>,
+{
+    if in_transaction() {
+        return future.await;
+    }
+
+    let tx_ctx = TransactionContext::begin_with(pool, options).await?;
+    let ambient = AmbientTransaction {
+        inner: Arc::new(Mutex::new(Some(tx_ctx))),
+    };
+    let result = CURRENT.scope(ambient.clone(), future).await;
+
+    // Handles cloned out of the scope see the transaction as consumed from here on.
+    let tx_ctx = ambient.inner.lock().await.take();
+    match tx_ctx {
+        Some(tx_ctx) => crate::executor::finish(tx_ctx, result).await,
+        None => Err(crate::Error::AlreadyConsumed),
+    }
+}
+
+/// Returns `true` if the current task is inside an ambient transaction scope.
+pub fn in_transaction() -> bool {
+    CURRENT.try_with(|_| ()).is_ok()
+}
+
+/// Returns the current task's ambient transaction.
+///
+/// Returns [`Error::NoAmbientTransaction`](crate::Error::NoAmbientTransaction) outside of
+/// a [`with_transaction`] scope.
+pub fn current_transaction() -> crate::Result<AmbientTransaction> {
+    CURRENT
+        .try_with(AmbientTransaction::clone)
+        .map_err(|_| crate::Error::NoAmbientTransaction)
+}
+
+/// Returns a connection to run queries on: the ambient transaction if there is one,
+/// otherwise `pool`.
+///
+/// The ambient transaction stays locked until the returned value is dropped.
+pub async fn current_or_pool(pool: &MySqlPool) -> crate::Result<AmbientConnection<'_>> {
+    match current_transaction() {
+        Ok(ambient) => Ok(AmbientConnection::Transaction(ambient.lock().await?)),
+        Err(_) => Ok(AmbientConnection::Pool(pool)),
+    }
+}
+
+/// Handle to an ambient transaction, returned by [`current_transaction`].
+///
+/// Handles are cheap to clone and share the same transaction.
+#[derive(Clone)]
+pub struct AmbientTransaction {
+    inner: Arc<Mutex<Option<TransactionContext<'static>>>>,
+}
+
+impl AmbientTransaction {
+    /// Locks the transaction for running queries.
+    ///
+    /// Other holders of the transaction wait until the returned guard is dropped, so do
+    /// not hold it across calls that lock the transaction again.
+    ///
+    /// Returns [`Error::AlreadyConsumed`](crate::Error::AlreadyConsumed) if the scope that
+    /// began the transaction has already finished.
+    pub async fn lock(&self) -> crate::Result<AmbientGuard> {
+        let guard = Arc::clone(&self.inner).lock_owned().await;
+        if guard.is_none() {
+            return Err(crate::Error::AlreadyConsumed);
+        }
+        Ok(AmbientGuard { guard })
+    }
+}
+
+impl std::fmt::Debug for AmbientTransaction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AmbientTransaction").finish_non_exhaustive()
+    }
+}
+
+/// Exclusive access to an ambient transaction, returned by [`AmbientTransaction::lock`].
+///
+/// Dereferences to the [`TransactionContext`].
+pub struct AmbientGuard {
+    guard: OwnedMutexGuard<Option<TransactionContext<'static>>>,
+}
+
+impl Deref for AmbientGuard {
+    type Target = TransactionContext<'static>;
+
+    fn deref(&self) -> &Self::Target {
+        self.guard
+            .as_ref()
+            .expect("ambient transaction checked when locked")
+    }
+}
+
+impl DerefMut for AmbientGuard {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.guard
+            .as_mut()
+            .expect("ambient transaction checked when locked")
+    }
+}
+
+impl std::fmt::Debug for AmbientGuard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.deref().fmt(f)
+    }
+}
+
+/// Either the ambient transaction or the pool, returned by [`current_or_pool`].
+///
+/// `&mut AmbientConnection` is an [`Executor`], so it can be passed to sqlx queries.
+#[derive(Debug)]
+pub enum AmbientConnection<'p> {
+    /// The locked ambient transaction
+    Transaction(AmbientGuard),
+    /// The pool, used when no ambient transaction is active
+    Pool(&'p MySqlPool),
+}
+
+impl AmbientConnection<'_> {
+    /// Returns `true` if queries run inside the ambient transaction.
+    pub fn is_transaction(&self) -> bool {
+        matches!(self, AmbientConnection::Transaction(_))
+    }
+}
+
+impl<'c, 'p: 'c> Executor<'c> for &'c mut AmbientConnection<'p> {
+    type Database = MySql;
+
+    fn fetch_many<'e, 'q, E>(
+        self,
+        query: E,
+    ) -> BoxStream<'e, Result<Either<MySqlQueryResult, MySqlRow>, sqlx::Error>>
+    where
+        'c: 'e,
+        E: Execute<'q, Self::Database>,
+        'q: 'e,
+        E: 'q,
+    {
+        match self {
+            AmbientConnection::Transaction(guard) => (&mut **guard).fetch_many(query),
+            AmbientConnection::Pool(pool) => (*pool).fetch_many(query),
+        }
+    }
+
+    fn fetch_optional<'e, 'q, E>(
+        self,
+        query: E,
+    ) -> BoxFuture<'e, Result<Option<MySqlRow>, sqlx::Error>>
+    where
+        'c: 'e,
+        E: Execute<'q, Self::Database>,
+        'q: 'e,
+        E: 'q,
+    {
+        match self {
+            AmbientConnection::Transaction(guard) => (&mut **guard).fetch_optional(query),
+            AmbientConnection::Pool(pool) => (*pool).fetch_optional(query),
+        }
+    }
+
+    fn prepare_with<'e, 'q: 'e>(
+        self,
+        sql: &'q str,
+        parameters: &'e [MySqlTypeInfo],
+    ) -> BoxFuture<'e, Result<MySqlStatement<'q>, sqlx::Error>>
+    where
+        'c: 'e,
+    {
+        match self {
+            AmbientConnection::Transaction(guard) => (&mut **guard).prepare_with(sql, parameters),
+            AmbientConnection::Pool(pool) => (*pool).prepare_with(sql, parameters),
+        }
+    }
+
+    fn describe<'e, 'q: 'e>(
+        self,
+        sql: &'q str,
+    ) -> BoxFuture<'e, Result<Describe<Self::Database>, sqlx::Error>>
+    where
+        'c: 'e,
+    {
+        match self {
+            AmbientConnection::Transaction(guard) => (&mut **guard).describe(sql),
+            AmbientConnection::Pool(pool) => (*pool).describe(sql),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_send<T: Send>(_: &T) {}
+
+    #[tokio::test]
+    async fn test_scope_futures_are_send() {
+        let pool = MySqlPool::connect_lazy("mysql://localhost/test").unwrap();
+        let scope = with_transaction(&pool, async {
+            let mut conn = current_or_pool(&pool).await?;
+            sqlx::query("SELECT 1").execute(&mut conn).await?;
+            Ok(())
+        });
+        assert_send(&scope);
+    }
+
+    #[tokio::test]
+    async fn test_outside_a_scope() {
+        assert!(!in_transaction());
+        let error = current_transaction().unwrap_err();
+        assert_eq!(error.kind(), "no_ambient_transaction");
+
+        let pool = MySqlPool::connect_lazy("mysql://localhost/test").unwrap();
+        let conn = current_or_pool(&pool).await.unwrap();
+        assert!(!conn.is_transaction());
+    }
+
+    #[tokio::test]
+    async fn test_finished_transaction_is_consumed() {
+        let finished = AmbientTransaction {
+            inner: Arc::new(Mutex::new(None)),
+        };
+        CURRENT
+            .scope(finished, async {
+                assert!(in_transaction());
+                let error = current_transaction().unwrap().lock().await.unwrap_err();
+                assert_eq!(error.kind(), "already_consumed");
+            })
+            .await;
+    }
+}
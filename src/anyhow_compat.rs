@@ -24,9 +24,9 @@ use std::pin::Pin;
 ///
 /// # Examples
 ///
-/// ```rust,no_run
+/// ```rust,ignore
 /// use sqlx::MySqlPool;
-/// use sqlx_transaction_manager::with_transaction_anyhow;
+/// use sqlx_transaction_manager::anyhow_compat::with_transaction_anyhow;
 /// use sqlx_named_bind::PreparedQuery;
 ///
 /// # async fn example() -> anyhow::Result<()> {
@@ -79,7 +79,7 @@ where
 ///
 /// ```rust,no_run
 /// use sqlx::MySqlPool;
-/// use sqlx_transaction_manager::{with_transaction_anyhow, with_nested_transaction_anyhow};
+/// use sqlx_transaction_manager::anyhow_compat::{with_nested_transaction_anyhow, with_transaction_anyhow};
 ///
 /// # async fn example() -> anyhow::Result<()> {
 /// # let pool = MySqlPool::connect("mysql://localhost/test").await?;
@@ -0,0 +1,264 @@
+//! Per-request transactions for [Axum](https://docs.rs/axum).
+//!
+//! Requires the `axum` feature. [`transaction_middleware`] begins a transaction for every
+//! request from the `MySqlPool` in the router's state, and handlers reach it through the
+//! [`Tx`] extractor. The transaction is committed when the response status is 2xx and
+//! rolled back otherwise, unless the handler decides with [`Tx::set_commit`].
+//!
+//! To begin the transactions with [`TransactionOptions`], install
+//! [`transaction_middleware_with`] with a [`TransactionState`] instead.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use axum::{middleware, routing::post, Router};
+//! use sqlx::MySqlPool;
+//! use sqlx_transaction_manager::axum::{transaction_middleware, Tx};
+//!
+//! async fn create_user(mut tx: Tx) -> Result<String, String> {
+//!     let result = sqlx::query("INSERT INTO users (name) VALUES (?)")
+//!         .bind("Alice")
+//!         .execute(&mut *tx)
+//!         .await
+//!         .map_err(|e| e.to_string())?;
+//!     let id = result.last_insert_id();
+//!     tx.after_commit(async move { println!("user {id} created") });
+//!     Ok(id.to_string())
+//! }
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let pool = MySqlPool::connect("mysql://localhost/test").await?;
+//! let app: Router = Router::new()
+//!     .route("/users", post(create_user))
+//!     .layer(middleware::from_fn_with_state(pool.clone(), transaction_middleware))
+//!     .with_state(pool);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! With options:
+//!
+//! ```rust,no_run
+//! use axum::{middleware, routing::post, Router};
+//! use sqlx::MySqlPool;
+//! use sqlx_transaction_manager::axum::{transaction_middleware_with, TransactionState};
+//! use sqlx_transaction_manager::{IsolationLevel, TransactionOptions};
+//!
+//! # async fn create_user() {}
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let pool = MySqlPool::connect("mysql://localhost/test").await?;
+//! let state = TransactionState::new(pool.clone()).options(
+//!     TransactionOptions::new()
+//!         .label("http")
+//!         .isolation_level(IsolationLevel::ReadCommitted),
+//! );
+//! let app: Router = Router::new()
+//!     .route("/users", post(create_user))
+//!     .layer(middleware::from_fn_with_state(state, transaction_middleware_with))
+//!     .with_state(pool);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::context::TransactionContext;
+use crate::options::TransactionOptions;
+use crate::request::{RequestTransaction, RequestTransactionGuard};
+use ::axum::extract::{FromRequestParts, Request, State};
+use ::axum::http::request::Parts;
+use ::axum::http::StatusCode;
+use ::axum::middleware::Next;
+use ::axum::response::{IntoResponse, Response};
+use sqlx::MySqlPool;
+use std::fmt;
+
+/// Middleware that runs every request in a transaction.
+///
+/// Install it with [`axum::middleware::from_fn_with_state`];
+/// the state only has to provide a `MySqlPool` through `FromRef`.
+///
+/// After the handler returns, the transaction is committed if the handler asked for it
+/// with [`Tx::set_commit`], or, if it did not decide, when the response status is 2xx.
+/// Otherwise it is rolled back. Work registered with [`Tx::after_commit`] runs once the
+/// commit succeeds, before the response is sent.
+///
+/// Responds with `503 Service Unavailable` if the transaction cannot be begun, and with
+/// `500 Internal Server Error` if the commit fails.
+pub async fn transaction_middleware(
+    State(pool): State<MySqlPool>,
+    request: Request,
+    next: Next,
+) -> Response {
+    run(&pool, TransactionOptions::default(), request, next).await
+}
+
+/// Middleware that runs every request in a transaction begun with the options in its
+/// [`TransactionState`].
+///
+/// Install it with [`axum::middleware::from_fn_with_state`], passing a
+/// [`TransactionState`]. Otherwise behaves like [`transaction_middleware`].
+pub async fn transaction_middleware_with(
+    State(state): State<TransactionState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    run(&state.pool, state.options, request, next).await
+}
+
+async fn run(
+    pool: &MySqlPool,
+    options: TransactionOptions,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let tx = match TransactionContext::begin_with(pool, options).await {
+        Ok(tx) => RequestTransaction::new(tx),
+        Err(_) => return StatusCode::SERVICE_UNAVAILABLE.into_response(),
+    };
+    request.extensions_mut().insert(tx.clone());
+
+    let response = next.run(request).await;
+    match tx.finish(response.status().is_success()).await {
+        Ok(()) => response,
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+/// State for [`transaction_middleware_with`]: the pool to begin transactions on, and the
+/// options to begin them with.
+#[derive(Debug, Clone)]
+pub struct TransactionState {
+    pool: MySqlPool,
+    options: TransactionOptions,
+}
+
+impl TransactionState {
+    /// Creates a state beginning transactions on `pool` with default options.
+    pub fn new(pool: MySqlPool) -> Self {
+        Self {
+            pool,
+            options: TransactionOptions::default(),
+        }
+    }
+
+    /// Sets the options every transaction is begun with.
+    pub fn options(mut self, options: TransactionOptions) -> Self {
+        self.options = options;
+        self
+    }
+}
+
+/// Extractor for the request's transaction, begun by [`transaction_middleware`] or
+/// [`transaction_middleware_with`].
+///
+/// Dereferences to the [`TransactionContext`], so `&mut *tx` can be passed to sqlx
+/// queries. A handler can extract it only once; the transaction is finished after the
+/// handler returns.
+pub type Tx = RequestTransactionGuard;
+
+impl<S: Send + Sync> FromRequestParts<S> for RequestTransactionGuard {
+    type Rejection = TxRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let tx = parts
+            .extensions
+            .get::<RequestTransaction>()
+            .ok_or(TxRejection::MissingMiddleware)?;
+        match tx.try_lock() {
+            Some(Ok(tx)) => Ok(tx),
+            Some(Err(_)) | None => Err(TxRejection::AlreadyExtracted),
+        }
+    }
+}
+
+/// Rejection returned when [`Tx`] cannot be extracted.
+///
+/// Both cases are programming errors and respond with `500 Internal Server Error`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TxRejection {
+    /// The route is not wrapped in [`transaction_middleware`]
+    MissingMiddleware,
+    /// The transaction is held by another extractor, or has already been finished
+    AlreadyExtracted,
+}
+
+impl fmt::Display for TxRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TxRejection::MissingMiddleware => {
+                f.write_str("no request transaction; is `transaction_middleware` installed?")
+            }
+            TxRejection::AlreadyExtracted => {
+                f.write_str("the request transaction has already been extracted")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TxRejection {}
+
+impl IntoResponse for TxRejection {
+    fn into_response(self) -> Response {
+        (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::axum::body::Body;
+    use ::axum::routing::get;
+    use ::axum::{middleware, Router};
+    use ::tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_extractor_requires_middleware() {
+        let app: Router = Router::new().route("/", get(|_tx: Tx| async { "ok" }));
+        let response = app
+            .oneshot(Request::get("/").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    fn unreachable_pool() -> MySqlPool {
+        sqlx::mysql::MySqlPoolOptions::new()
+            .acquire_timeout(std::time::Duration::from_millis(50))
+            .connect_lazy("mysql://localhost:1/test")
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_unavailable_when_begin_fails() {
+        let pool = unreachable_pool();
+        let app: Router = Router::new()
+            .route("/", get(|_tx: Tx| async { "ok" }))
+            .layer(middleware::from_fn_with_state(
+                pool.clone(),
+                transaction_middleware,
+            ))
+            .with_state(pool);
+        let response = app
+            .oneshot(Request::get("/").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn test_middleware_with_options() {
+        let state = TransactionState::new(unreachable_pool())
+            .options(TransactionOptions::new().label("http").read_only());
+        let app: Router = Router::new()
+            .route("/", get(|_tx: Tx| async { "ok" }))
+            .layer(middleware::from_fn_with_state(
+                state,
+                transaction_middleware_with,
+            ));
+        let response = app
+            .oneshot(Request::get("/").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+}
@@ -0,0 +1,153 @@
+//! sqlcommenter-style SQL comments for statements run inside a transaction.
+//!
+//! Enable them with [`TransactionOptions::sql_comment`](crate::TransactionOptions::sql_comment).
+
+use futures_core::stream::Stream;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Key-value tags prepended as a comment to every statement a transaction runs.
+///
+/// Tags are rendered in the [sqlcommenter](https://google.github.io/sqlcommenter/spec/)
+/// format, `/*key='value',...*/`, with keys sorted and keys and values percent-encoded.
+/// The transaction's label, if any, is added as the `transaction` tag unless that tag is
+/// set explicitly.
+///
+/// The comment changes the statement text, and per-request values such as a request ID
+/// or trace ID make every tagged statement unique. Tagged statements with bind
+/// arguments are therefore prepared without being cached, as with
+/// `Query::persistent(false)`, and closed after running, so they never fill the
+/// connection's statement cache or the server's `max_prepared_stmt_count`. The price is
+/// an extra prepare round trip per statement; leave tagging off for hot paths where
+/// that matters.
+///
+/// # Examples
+///
+/// ```rust
+/// use sqlx_transaction_manager::{SqlComment, TransactionOptions};
+///
+/// let options = TransactionOptions::new()
+///     .label("checkout.place_order")
+///     .sql_comment(
+///         SqlComment::new()
+///             .tag("request_id", "7f3a")
+///             .tag("traceparent", "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"),
+///     );
+/// // Statements run as:
+/// // /*request_id='7f3a',traceparent='00-4bf9...-01',transaction='checkout.place_order'*/ SELECT ...
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SqlComment {
+    tags: BTreeMap<String, String>,
+}
+
+impl SqlComment {
+    /// Creates a comment with no tags.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a tag, replacing any previous value for `key`.
+    pub fn tag(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.tags.insert(key.into(), value.into());
+        self
+    }
+
+    /// Renders the comment, followed by a space, to prepend to statements.
+    pub(crate) fn render(&self, label: Option<&str>) -> Option<String> {
+        let label = label.filter(|_| !self.tags.contains_key("transaction"));
+        let mut tags: Vec<(&str, &str)> = self
+            .tags
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+            .chain(label.map(|label| ("transaction", label)))
+            .collect();
+        if tags.is_empty() {
+            return None;
+        }
+        tags.sort_unstable();
+
+        let mut comment = String::from("/*");
+        for (i, (key, value)) in tags.into_iter().enumerate() {
+            if i > 0 {
+                comment.push(',');
+            }
+            encode(&mut comment, key);
+            comment.push_str("='");
+            encode(&mut comment, value);
+            comment.push('\'');
+        }
+        comment.push_str("*/ ");
+        Some(comment)
+    }
+}
+
+/// Percent-encodes everything but unreserved characters, so values can never close
+/// the comment or break out of their quotes.
+fn encode(out: &mut String, s: &str) {
+    for byte in s.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(byte as char)
+            }
+            _ => {
+                let _ = write!(out, "%{byte:02X}");
+            }
+        }
+    }
+}
+
+/// A stream yielding a single error, for statements whose arguments failed to encode.
+pub(crate) struct ErrorStream<T> {
+    error: Option<sqlx::Error>,
+    _item: PhantomData<fn() -> T>,
+}
+
+impl<T> ErrorStream<T> {
+    pub(crate) fn new(error: sqlx::Error) -> Self {
+        Self {
+            error: Some(error),
+            _item: PhantomData,
+        }
+    }
+}
+
+impl<T> Stream for ErrorStream<T> {
+    type Item = Result<T, sqlx::Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Poll::Ready(self.error.take().map(Err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_sorts_and_encodes_tags() {
+        let comment = SqlComment::new()
+            .tag("route", "/orders/{id}")
+            .tag("request_id", "it's */ done");
+        assert_eq!(
+            comment.render(Some("checkout.place_order")).as_deref(),
+            Some(
+                "/*request_id='it%27s%20%2A%2F%20done',route='%2Forders%2F%7Bid%7D',\
+                 transaction='checkout.place_order'*/ "
+            )
+        );
+    }
+
+    #[test]
+    fn test_explicit_transaction_tag_wins_over_label() {
+        let comment = SqlComment::new().tag("transaction", "manual");
+        assert_eq!(
+            comment.render(Some("checkout")).as_deref(),
+            Some("/*transaction='manual'*/ ")
+        );
+        assert_eq!(SqlComment::new().render(None), None);
+    }
+}
@@ -1,5 +1,22 @@
-use sqlx::{MySql, MySqlConnection, MySqlPool, Transaction};
-use std::ops::DerefMut;
+use crate::comment::ErrorStream;
+use crate::deadlock;
+use crate::dropped::{DropPolicy, DroppedTransaction};
+use crate::error::ErrorContext;
+use crate::instrument::{Instrumentation, RollbackReason};
+use crate::journal::{Journal, StatementJournal};
+use crate::limits::LimitState;
+use crate::options::TransactionOptions;
+use crate::registry::Registration;
+use crate::verification::{self, CommitOutcome, PendingCommitCheck};
+use futures_core::future::BoxFuture;
+use futures_core::stream::BoxStream;
+use sqlx::mysql::{MySqlQueryResult, MySqlRow, MySqlStatement, MySqlTypeInfo};
+use sqlx::{
+    Acquire, Describe, Either, Execute, Executor, MySql, MySqlConnection, MySqlPool, Transaction,
+};
+use std::borrow::Cow;
+use std::fmt;
+use std::time::{Duration, Instant};
 
 /// Transaction context wrapper providing type-safe transaction boundaries.
 ///
@@ -31,6 +48,20 @@ use std::ops::DerefMut;
 /// ```
 pub struct TransactionContext<'tx> {
     tx: Option<Transaction<'tx, MySql>>,
+    commit_check: Option<PendingCommitCheck>,
+    label: Option<Cow<'static, str>>,
+    started_at: Instant,
+    savepoint_depth: usize,
+    instrumentation: Instrumentation,
+    limits: LimitState,
+    journal: Option<Journal>,
+    registration: Option<Registration>,
+    connection_id: Option<u64>,
+    trx_id: Option<u64>,
+    deadlock_pool: Option<MySqlPool>,
+    sql_comment: Option<String>,
+    tagged_sql: String,
+    on_drop: DropPolicy,
 }
 
 impl<'tx> TransactionContext<'tx> {
@@ -55,18 +86,346 @@ impl<'tx> TransactionContext<'tx> {
     /// # }
     /// ```
     pub async fn begin(pool: &MySqlPool) -> crate::Result<Self> {
-        Ok(Self {
-            tx: Some(pool.begin().await?),
+        Self::begin_with(pool, TransactionOptions::default()).await
+    }
+
+    /// Begins a new transaction from the connection pool using the given options.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the transaction cannot be started, or if a commit marker
+    /// is configured and cannot be written.
+    ///
+    /// Commit markers are not written for read-only transactions.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use sqlx::MySqlPool;
+    /// use sqlx_transaction_manager::{CommitMarker, TransactionContext, TransactionOptions};
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let pool = MySqlPool::connect("mysql://localhost/test").await?;
+    /// let options = TransactionOptions::new()
+    ///     .verify_commit(CommitMarker::new("tx_commit_markers"));
+    /// let mut tx = TransactionContext::begin_with(&pool, options).await?;
+    /// // Use the transaction...
+    /// tx.commit().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn begin_with(pool: &MySqlPool, options: TransactionOptions) -> crate::Result<Self> {
+        Self::start(pool, &options)
+            .await
+            .map_err(|e| Self::begin_failed(&options, e))
+    }
+
+    /// Begins a new transaction on a connection the caller already holds.
+    ///
+    /// Use it when statements must run on a specific connection, for example one whose
+    /// session variables have been set or which holds a named lock. A
+    /// `PoolConnection<MySql>` can be passed as `&mut conn`. The connection stays
+    /// borrowed until the transaction is committed, rolled back or dropped, and the usual
+    /// rollback-on-drop guarantee applies.
+    ///
+    /// If the connection is already in a transaction, a savepoint is created instead.
+    /// This only holds with default options, see [`begin_on_with`](Self::begin_on_with).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the transaction cannot be started.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use sqlx::MySqlPool;
+    /// use sqlx_transaction_manager::TransactionContext;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let pool = MySqlPool::connect("mysql://localhost/test").await?;
+    /// let mut conn = pool.acquire().await?;
+    /// sqlx::query("SET SESSION time_zone = '+00:00'")
+    ///     .execute(&mut *conn)
+    ///     .await?;
+    ///
+    /// let mut tx = TransactionContext::begin_on(&mut conn).await?;
+    /// sqlx::query("INSERT INTO events (at) VALUES (NOW())")
+    ///     .execute(tx.as_executor())
+    ///     .await?;
+    /// tx.commit().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn begin_on(conn: &'tx mut MySqlConnection) -> crate::Result<Self> {
+        Self::begin_on_with(conn, TransactionOptions::default()).await
+    }
+
+    /// Begins a new transaction on a connection the caller already holds, using the
+    /// given options.
+    ///
+    /// See [`begin_on`](Self::begin_on). Commit markers and deadlock diagnostics need a
+    /// pool to open a second connection, so they are not used for transactions begun
+    /// this way.
+    ///
+    /// An isolation level or read-only access can only be set when a transaction is
+    /// started, so with either option the connection must not already be in a
+    /// transaction; no savepoint is created in its place.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the transaction cannot be started, including when the
+    /// connection is already in a transaction and the options set an isolation level or
+    /// read-only access.
+    pub async fn begin_on_with(
+        conn: &'tx mut MySqlConnection,
+        options: TransactionOptions,
+    ) -> crate::Result<Self> {
+        Self::start_on(conn, &options)
+            .await
+            .map_err(|e| Self::begin_failed(&options, e))
+    }
+
+    /// Begins a new transaction from the connection pool, labeled with the business
+    /// operation it belongs to.
+    ///
+    /// Shorthand for [`begin_with`](Self::begin_with) with
+    /// [`TransactionOptions::label`]. The label is readable through [`label`](Self::label),
+    /// attached to every error returned for this transaction and to emitted telemetry.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database connection fails or transaction cannot be started.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use sqlx::MySqlPool;
+    /// use sqlx_transaction_manager::TransactionContext;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let pool = MySqlPool::connect("mysql://localhost/test").await?;
+    /// let mut tx = TransactionContext::begin_labeled(&pool, "checkout.place_order").await?;
+    /// assert_eq!(tx.label(), Some("checkout.place_order"));
+    /// tx.commit().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn begin_labeled(
+        pool: &MySqlPool,
+        label: impl Into<Cow<'static, str>>,
+    ) -> crate::Result<Self> {
+        Self::begin_with(pool, TransactionOptions::new().label(label)).await
+    }
+
+    async fn start(pool: &MySqlPool, options: &TransactionOptions) -> crate::Result<Self> {
+        let tx = match options.begin_statement() {
+            Some(statement) => pool.begin_with(statement).await?,
+            None => pool.begin().await?,
+        };
+        Self::init(tx, options, Some(pool)).await
+    }
+
+    async fn start_on(
+        conn: &'tx mut MySqlConnection,
+        options: &TransactionOptions,
+    ) -> crate::Result<Self> {
+        let tx = Transaction::begin(conn, options.begin_statement().map(Cow::Owned)).await?;
+        Self::init(tx, options, None).await
+    }
+
+    async fn init(
+        tx: Transaction<'tx, MySql>,
+        options: &TransactionOptions,
+        pool: Option<&MySqlPool>,
+    ) -> crate::Result<Self> {
+        let mut ctx = Self::from_transaction(tx, options, pool);
+        match ctx.set_up(options, pool).await {
+            Ok(()) => Ok(ctx),
+            Err(e) => {
+                // The caller reports this as a failed begin, so roll back here instead
+                // of letting `Drop` report a dropped transaction.
+                if let Some(tx) = ctx.tx.take() {
+                    let _ = tx.rollback().await;
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Runs the statements some options need right after `BEGIN`.
+    async fn set_up(
+        &mut self,
+        options: &TransactionOptions,
+        pool: Option<&MySqlPool>,
+    ) -> crate::Result<()> {
+        if options.capture_connection_id {
+            self.connection_id().await?;
+        }
+
+        if let (Some(pool), Some(marker), false) = (pool, &options.commit_marker, options.read_only)
+        {
+            self.commit_check = Some(marker.write(pool, self.as_executor()).await?);
+        }
+
+        Ok(())
+    }
+
+    fn begin_failed(options: &TransactionOptions, error: crate::Error) -> crate::Error {
+        Instrumentation::begin_failed(options, &error);
+        error.with_context(ErrorContext {
+            label: options.label.clone(),
+            ..ErrorContext::default()
         })
     }
 
+    fn from_transaction(
+        tx: Transaction<'tx, MySql>,
+        options: &TransactionOptions,
+        pool: Option<&MySqlPool>,
+    ) -> Self {
+        let started_at = Instant::now();
+        Self {
+            tx: Some(tx),
+            commit_check: None,
+            label: options.label.clone(),
+            started_at,
+            savepoint_depth: 0,
+            instrumentation: Instrumentation::began(options, pool),
+            limits: LimitState::new(options.limits.clone()),
+            journal: options
+                .journal_capacity
+                .map(|capacity| Journal::new(capacity, started_at)),
+            registration: options
+                .registry
+                .as_ref()
+                .map(|registry| registry.register(options.label.clone())),
+            connection_id: None,
+            trx_id: None,
+            deadlock_pool: pool.filter(|_| options.diagnose_deadlocks).cloned(),
+            sql_comment: options
+                .sql_comment
+                .as_ref()
+                .and_then(|comment| comment.render(options.label.as_deref())),
+            tagged_sql: String::new(),
+            on_drop: options.on_drop.clone(),
+        }
+    }
+
+    /// Returns the label attached to this transaction, if any.
+    ///
+    /// See [`TransactionOptions::label`].
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    /// Returns how long the transaction has been open.
+    pub fn elapsed(&self) -> Duration {
+        self.started_at.elapsed()
+    }
+
+    /// Returns the number of nested transactions (savepoints) currently open.
+    pub fn savepoint_depth(&self) -> usize {
+        self.savepoint_depth
+    }
+
+    /// Returns the commit marker written for this transaction, if verification is enabled.
+    pub fn commit_marker(&self) -> Option<&str> {
+        self.commit_check.as_ref().map(PendingCommitCheck::id)
+    }
+
+    /// Returns the identifier of this transaction in its registry, if one is configured.
+    ///
+    /// Matches [`LiveTransaction::id`](crate::LiveTransaction::id); see
+    /// [`TransactionOptions::registry`].
+    pub fn registry_id(&self) -> Option<u64> {
+        self.registration.as_ref().map(Registration::id)
+    }
+
+    /// Returns the MySQL `CONNECTION_ID()` of the transaction's connection.
+    ///
+    /// This is the `ID` column of the processlist and the `trx_mysql_thread_id` column
+    /// of `INFORMATION_SCHEMA.INNODB_TRX`. It is fetched on first use and cached; once
+    /// fetched it is included in errors returned for the transaction and in its
+    /// `tracing` span.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails, or
+    /// [`Error::AlreadyConsumed`](crate::Error::AlreadyConsumed) if the ID was not fetched
+    /// before the transaction was consumed.
+    pub async fn connection_id(&mut self) -> crate::Result<u64> {
+        if let Some(id) = self.connection_id {
+            return Ok(id);
+        }
+
+        let result: Result<(u64,), _> = sqlx::query_as("SELECT CAST(CONNECTION_ID() AS UNSIGNED)")
+            .fetch_one(self.try_as_executor()?)
+            .await;
+        let (id,) = result.map_err(|e| self.annotate_error(e.into()))?;
+        self.connection_id = Some(id);
+        self.instrumentation
+            .server_ids(self.connection_id, self.trx_id);
+        Ok(id)
+    }
+
+    /// Returns the InnoDB transaction ID, as shown in the `trx_id` column of
+    /// `INFORMATION_SCHEMA.INNODB_TRX`.
+    ///
+    /// InnoDB registers a transaction there only once it has run a statement, so this
+    /// returns `Ok(None)` before that. The ID is cached once found; note that InnoDB
+    /// assigns a new ID when a transaction that has only read so far makes its first
+    /// write, so fetch it after writing when correlating with lock waits. Once fetched
+    /// it is included in errors returned for the transaction and in its `tracing` span.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails, or
+    /// [`Error::AlreadyConsumed`](crate::Error::AlreadyConsumed) if the ID was not fetched
+    /// before the transaction was consumed.
+    pub async fn innodb_trx_id(&mut self) -> crate::Result<Option<u64>> {
+        if let Some(id) = self.trx_id {
+            return Ok(Some(id));
+        }
+
+        let result: Result<Option<(u64,)>, _> = sqlx::query_as(
+            "SELECT CAST(trx_id AS UNSIGNED) FROM information_schema.innodb_trx \
+             WHERE trx_mysql_thread_id = CONNECTION_ID()",
+        )
+        .fetch_optional(self.try_as_executor()?)
+        .await;
+        let id = result
+            .map_err(|e| self.annotate_error(e.into()))?
+            .map(|(id,)| id);
+        self.trx_id = id;
+        self.instrumentation
+            .server_ids(self.connection_id, self.trx_id);
+        Ok(id)
+    }
+
+    /// Returns the statements recorded so far, if the journal is enabled.
+    ///
+    /// See [`TransactionOptions::journal`].
+    pub fn journal(&self) -> Option<StatementJournal> {
+        self.journal.as_ref().map(Journal::snapshot)
+    }
+
     /// Commits the transaction.
     ///
     /// After calling this method, the `TransactionContext` is consumed and cannot be used.
     ///
+    /// If commit verification is enabled and the commit fails without a response from
+    /// the server, the commit marker is checked on a fresh pool connection. A marker that
+    /// is found means the transaction was applied and `Ok(())` is returned.
+    ///
     /// # Errors
     ///
-    /// Returns an error if the commit operation fails.
+    /// Returns an error if the commit operation fails. When verification could not
+    /// confirm the commit, the error is [`Error::AmbiguousCommit`](crate::Error::AmbiguousCommit)
+    /// carrying a [`CommitOutcome`] that tells whether retrying is safe.
+    ///
+    /// If the transaction has been open longer than
+    /// [`TransactionOptions::max_duration`], it is rolled back instead and
+    /// [`Error::TimeLimitExceeded`](crate::Error::TimeLimitExceeded) is returned.
     ///
     /// # Examples
     ///
@@ -83,10 +442,39 @@ impl<'tx> TransactionContext<'tx> {
     /// # }
     /// ```
     pub async fn commit(mut self) -> crate::Result<()> {
-        if let Some(tx) = self.tx.take() {
-            tx.commit().await?;
+        let Some(tx) = self.tx.take() else {
+            return Ok(());
+        };
+
+        let elapsed = self.elapsed();
+        self.check_limits(elapsed);
+        if let Some(limit) = self.limits.exceeded(elapsed) {
+            return self.abort_over_limit(tx, elapsed, limit).await;
         }
-        Ok(())
+
+        let committed = self
+            .instrumentation
+            .statement("COMMIT")
+            .future(Box::pin(tx.commit()))
+            .await;
+        let result = match (committed, self.commit_check.take()) {
+            (Ok(()), _) => Ok(()),
+            (Err(e), Some(check)) if verification::is_ambiguous(&e) => match check.check().await {
+                CommitOutcome::Committed => Ok(()),
+                outcome => Err(crate::Error::AmbiguousCommit { outcome, source: e }),
+            },
+            (Err(e), _) => Err(e.into()),
+        };
+        let result = match result {
+            Ok(()) => Ok(()),
+            Err(e) => Err(self.diagnose(e).await),
+        };
+
+        match &result {
+            Ok(()) => self.instrumentation.committed(self.elapsed()),
+            Err(e) => self.instrumentation.commit_failed(self.elapsed(), e),
+        }
+        result
     }
 
     /// Explicitly rolls back the transaction.
@@ -114,17 +502,179 @@ impl<'tx> TransactionContext<'tx> {
     /// ```
     pub async fn rollback(mut self) -> crate::Result<()> {
         if let Some(tx) = self.tx.take() {
-            tx.rollback().await?;
+            self.check_limits(self.elapsed());
+            let rolled_back = self
+                .instrumentation
+                .statement("ROLLBACK")
+                .future(Box::pin(tx.rollback()))
+                .await;
+            match rolled_back {
+                Ok(()) => self
+                    .instrumentation
+                    .rolled_back(self.elapsed(), RollbackReason::Explicit),
+                Err(e) => {
+                    let e = self.annotate_error(e.into());
+                    self.instrumentation.rollback_failed(self.elapsed(), &e);
+                    return Err(e);
+                }
+            }
         }
         Ok(())
     }
 
+    /// Rolls back a transaction that outlived its hard limit instead of committing it.
+    async fn abort_over_limit(
+        &self,
+        tx: Transaction<'tx, MySql>,
+        elapsed: Duration,
+        limit: Duration,
+    ) -> crate::Result<()> {
+        let rolled_back = self
+            .instrumentation
+            .statement("ROLLBACK")
+            .future(Box::pin(tx.rollback()))
+            .await;
+        let error = match rolled_back {
+            Ok(()) => crate::Error::TimeLimitExceeded { elapsed, limit },
+            Err(e) => e.into(),
+        };
+        let error = self.annotate_error(error);
+        self.instrumentation.commit_failed(self.elapsed(), &error);
+        Err(error)
+    }
+
+    /// Reports the transaction if it has been open longer than its warning threshold.
+    fn check_limits(&mut self, elapsed: Duration) {
+        if let Some(threshold) = self.limits.check(self.label.as_deref(), elapsed) {
+            self.instrumentation.long_open(elapsed, threshold);
+        }
+    }
+
+    /// Attaches this transaction's details to `error`, including a deadlock report if
+    /// [`TransactionOptions::diagnose_deadlocks`] is enabled and `error` is a deadlock.
+    ///
+    /// Errors returned by the `with_transaction` family and by [`commit`](Self::commit)
+    /// are already diagnosed; use this for errors from statements run by hand.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use sqlx::MySqlPool;
+    /// use sqlx_transaction_manager::{TransactionContext, TransactionOptions};
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let pool = MySqlPool::connect("mysql://localhost/test").await?;
+    /// let options = TransactionOptions::new().diagnose_deadlocks();
+    /// let mut tx = TransactionContext::begin_with(&pool, options).await?;
+    ///
+    /// let updated = sqlx::query("UPDATE accounts SET balance = 0 WHERE id = 1")
+    ///     .execute(&mut tx)
+    ///     .await;
+    /// if let Err(e) = updated {
+    ///     let e = tx.diagnose(e.into()).await;
+    ///     if let Some(report) = e.deadlock_report() {
+    ///         eprintln!("{report}");
+    ///     }
+    ///     return Err(e.into());
+    /// }
+    /// tx.commit().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn diagnose(&self, error: crate::Error) -> crate::Error {
+        with_deadlock_report(self.deadlock_pool.as_ref(), self.annotate_error(error)).await
+    }
+
+    /// Rolls back a transaction abandoned because of `error`, and returns the error
+    /// diagnosed like [`diagnose`](Self::diagnose).
+    ///
+    /// The rollback comes first, so the connection and any locks it still holds are
+    /// released before the deadlock report is fetched on another pool connection.
+    pub(crate) async fn abandon(mut self, error: crate::Error) -> crate::Error {
+        let error = self.annotate_error(error);
+        self.record_failure(&error);
+        let deadlock_pool = self.deadlock_pool.take();
+        let _ = self.rollback().await;
+        with_deadlock_report(deadlock_pool.as_ref(), error).await
+    }
+
+    /// Attaches this transaction's details, such as its label, to `error`.
+    pub(crate) fn annotate_error(&self, error: crate::Error) -> crate::Error {
+        error.with_context(ErrorContext {
+            label: self.label.clone(),
+            journal: self.journal(),
+            connection_id: self.connection_id,
+            trx_id: self.trx_id,
+            deadlock: None,
+        })
+    }
+
+    /// Records the error that is about to abandon this transaction.
+    pub(crate) fn record_failure(&self, error: &crate::Error) {
+        self.instrumentation.failed(error);
+    }
+
+    pub(crate) fn instrumentation(&self) -> &Instrumentation {
+        &self.instrumentation
+    }
+
+    /// Marks a savepoint as opened or closed, returning the depth of the innermost one.
+    pub(crate) fn enter_savepoint(&mut self) -> usize {
+        self.savepoint_depth += 1;
+        self.savepoint_depth
+    }
+
+    pub(crate) fn exit_savepoint(&mut self) {
+        self.savepoint_depth = self.savepoint_depth.saturating_sub(1);
+    }
+
+    /// Returns the connection, or `None` if the transaction has already been consumed.
+    fn connection(&mut self) -> Option<&mut MySqlConnection> {
+        if self.tx.is_some() {
+            self.check_limits(self.elapsed());
+        }
+        self.tx.as_deref_mut()
+    }
+
+    /// Returns the connection, and `sql` prefixed with the configured SQL comment, if any.
+    ///
+    /// The tagged statement is kept in a buffer on the context, so it lives as long as
+    /// the borrow of the connection. Returns `None` if the transaction has already been
+    /// consumed.
+    fn tagged_executor(&mut self, sql: &str) -> Option<(&mut MySqlConnection, Option<&str>)> {
+        if self.tx.is_some() {
+            self.check_limits(self.elapsed());
+        }
+
+        let Self {
+            tx,
+            sql_comment,
+            tagged_sql,
+            ..
+        } = self;
+        let conn = tx.as_deref_mut()?;
+        let tagged = match sql_comment {
+            Some(comment) => {
+                tagged_sql.clear();
+                tagged_sql.push_str(comment);
+                tagged_sql.push_str(sql);
+                Some(tagged_sql.as_str())
+            }
+            None => None,
+        };
+        Some((conn, tagged))
+    }
+
     /// Returns a mutable reference to the underlying connection for use as an Executor.
     ///
     /// This method provides access to `&mut MySqlConnection`, which implements SQLx's
     /// `Executor` trait. Use this when calling SQLx query methods or other libraries
     /// that accept an executor.
     ///
+    /// This is the legacy accessor. Passing `&mut tx` as the executor, or calling
+    /// [`try_as_executor`](Self::try_as_executor), reports a consumed transaction as an
+    /// error instead.
+    ///
     /// # Panics
     ///
     /// Panics if the transaction has already been consumed (committed or rolled back).
@@ -149,10 +699,52 @@ impl<'tx> TransactionContext<'tx> {
     /// # }
     /// ```
     pub fn as_executor(&mut self) -> &mut MySqlConnection {
-        self.tx
-            .as_mut()
+        self.connection()
             .expect("Transaction has already been consumed")
-            .deref_mut()
+    }
+
+    /// Returns a mutable reference to the underlying connection, or an error if the
+    /// transaction has already been consumed.
+    ///
+    /// This is the non-panicking counterpart of [`as_executor`](Self::as_executor).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::AlreadyConsumed`](crate::Error::AlreadyConsumed) if the transaction
+    /// has already been committed or rolled back.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use sqlx::MySqlPool;
+    /// use sqlx_transaction_manager::TransactionContext;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let pool = MySqlPool::connect("mysql://localhost/test").await?;
+    /// let mut tx = TransactionContext::begin(&pool).await?;
+    ///
+    /// sqlx::query("INSERT INTO users (name) VALUES (?)")
+    ///     .bind("Alice")
+    ///     .execute(tx.try_as_executor()?)
+    ///     .await?;
+    ///
+    /// tx.commit().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn try_as_executor(&mut self) -> crate::Result<&mut MySqlConnection> {
+        if !self.is_active() {
+            return Err(self.annotate_error(crate::Error::AlreadyConsumed));
+        }
+        Ok(self.as_executor())
+    }
+
+    /// Returns `true` if the transaction has not been committed or rolled back yet.
+    ///
+    /// Library code handed a context can check this before running statements,
+    /// instead of risking a panic from [`as_executor`](Self::as_executor).
+    pub fn is_active(&self) -> bool {
+        self.tx.is_some()
     }
 
     /// Consumes the context and returns the underlying SQLx `Transaction`.
@@ -185,6 +777,266 @@ impl<'tx> TransactionContext<'tx> {
             .take()
             .expect("Transaction has already been consumed")
     }
+
+    /// Consumes the context and returns the underlying SQLx `Transaction`, or an error
+    /// if the transaction has already been consumed.
+    ///
+    /// This is the non-panicking counterpart of [`into_inner`](Self::into_inner).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::AlreadyConsumed`](crate::Error::AlreadyConsumed) if the transaction
+    /// has already been committed or rolled back.
+    pub fn try_into_inner(mut self) -> crate::Result<Transaction<'tx, MySql>> {
+        match self.tx.take() {
+            Some(tx) => Ok(tx),
+            None => Err(self.annotate_error(crate::Error::AlreadyConsumed)),
+        }
+    }
+}
+
+/// Attaches the latest deadlock report to `error` if it is a deadlock and `pool` is set.
+async fn with_deadlock_report(pool: Option<&MySqlPool>, error: crate::Error) -> crate::Error {
+    match pool {
+        Some(pool) if error.is_deadlock() && error.deadlock_report().is_none() => {
+            let deadlock = deadlock::fetch(pool).await;
+            error.with_context(ErrorContext {
+                deadlock,
+                ..ErrorContext::default()
+            })
+        }
+        _ => error,
+    }
+}
+
+/// A [`TransactionContext`] that owns its pooled connection.
+///
+/// It borrows nothing, so it can be moved into `tokio::spawn` or stored in a struct
+/// without a lifetime parameter.
+pub type OwnedTransactionContext = TransactionContext<'static>;
+
+impl TransactionContext<'static> {
+    /// Begins a new transaction that owns its pooled connection.
+    ///
+    /// Equivalent to [`begin`](Self::begin), but the result is always an
+    /// [`OwnedTransactionContext`], which spares type annotations where the context is
+    /// spawned or stored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database connection fails or transaction cannot be started.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use sqlx::MySqlPool;
+    /// use sqlx_transaction_manager::TransactionContext;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let pool = MySqlPool::connect("mysql://localhost/test").await?;
+    /// let mut tx = TransactionContext::begin_owned(&pool).await?;
+    /// let handle = tokio::spawn(async move {
+    ///     sqlx::query("INSERT INTO users (name) VALUES (?)")
+    ///         .bind("Alice")
+    ///         .execute(tx.as_executor())
+    ///         .await?;
+    ///     tx.commit().await
+    /// });
+    /// handle.await??;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn begin_owned(pool: &MySqlPool) -> crate::Result<OwnedTransactionContext> {
+        Self::begin(pool).await
+    }
+
+    /// Begins a new transaction that owns its pooled connection, using the given options.
+    ///
+    /// See [`begin_owned`](Self::begin_owned) and [`begin_with`](Self::begin_with).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database connection fails or transaction cannot be started.
+    pub async fn begin_owned_with(
+        pool: &MySqlPool,
+        options: TransactionOptions,
+    ) -> crate::Result<OwnedTransactionContext> {
+        Self::begin_with(pool, options).await
+    }
+}
+
+impl fmt::Debug for TransactionContext<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TransactionContext")
+            .field("active", &self.is_active())
+            .field("label", &self.label())
+            .field("commit_marker", &self.commit_marker())
+            .finish()
+    }
+}
+
+/// Runs statements directly on the transaction, so `&mut TransactionContext` can be passed
+/// anywhere a `&mut MySqlConnection` or a `&mut Transaction` is accepted.
+///
+/// If the transaction has already been consumed, every method returns
+/// `sqlx::Error::Protocol` instead of running the statement.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use sqlx::{Executor, MySql, MySqlPool};
+/// use sqlx_transaction_manager::TransactionContext;
+///
+/// async fn insert_user<'e>(
+///     executor: impl Executor<'e, Database = MySql>,
+///     name: &str,
+/// ) -> sqlx::Result<()> {
+///     sqlx::query("INSERT INTO users (name) VALUES (?)")
+///         .bind(name)
+///         .execute(executor)
+///         .await?;
+///     Ok(())
+/// }
+///
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// # let pool = MySqlPool::connect("mysql://localhost/test").await?;
+/// let mut tx = TransactionContext::begin(&pool).await?;
+/// insert_user(&mut tx, "Alice").await?;
+/// tx.commit().await?;
+/// # Ok(())
+/// # }
+/// ```
+impl<'c, 'tx> Executor<'c> for &'c mut TransactionContext<'tx> {
+    type Database = MySql;
+
+    fn fetch_many<'e, 'q, E>(
+        self,
+        query: E,
+    ) -> BoxStream<'e, Result<Either<MySqlQueryResult, MySqlRow>, sqlx::Error>>
+    where
+        'c: 'e,
+        E: Execute<'q, Self::Database>,
+        'q: 'e,
+        E: 'q,
+    {
+        let sql = query.sql();
+        let span = self.instrumentation.statement(sql);
+        let journal = self.journal.clone();
+        let Some((conn, tagged)) = self.tagged_executor(sql) else {
+            return Box::pin(ErrorStream::new(consumed_error()));
+        };
+        let stream = match tagged {
+            Some(tagged) if query.statement().is_none() => {
+                let mut query = query;
+                match query.take_arguments() {
+                    // Tagged text is unique per comment, so it is never cached.
+                    Ok(Some(arguments)) => {
+                        conn.fetch_many(sqlx::query_with(tagged, arguments).persistent(false))
+                    }
+                    Ok(None) => conn.fetch_many(tagged),
+                    Err(e) => Box::pin(ErrorStream::new(sqlx::Error::Encode(e))),
+                }
+            }
+            _ => conn.fetch_many(query),
+        };
+        span.stream(match journal {
+            Some(journal) => journal.stream(sql, stream),
+            None => stream,
+        })
+    }
+
+    fn fetch_optional<'e, 'q, E>(
+        self,
+        query: E,
+    ) -> BoxFuture<'e, Result<Option<MySqlRow>, sqlx::Error>>
+    where
+        'c: 'e,
+        E: Execute<'q, Self::Database>,
+        'q: 'e,
+        E: 'q,
+    {
+        let sql = query.sql();
+        let span = self.instrumentation.statement(sql);
+        let journal = self.journal.clone();
+        let Some((conn, tagged)) = self.tagged_executor(sql) else {
+            return Box::pin(async { Err(consumed_error()) });
+        };
+        let future = match tagged {
+            Some(tagged) if query.statement().is_none() => {
+                let mut query = query;
+                match query.take_arguments() {
+                    // Tagged text is unique per comment, so it is never cached.
+                    Ok(Some(arguments)) => {
+                        conn.fetch_optional(sqlx::query_with(tagged, arguments).persistent(false))
+                    }
+                    Ok(None) => conn.fetch_optional(tagged),
+                    Err(e) => Box::pin(async move { Err(sqlx::Error::Encode(e)) }),
+                }
+            }
+            _ => conn.fetch_optional(query),
+        };
+        span.future(match journal {
+            Some(journal) => journal.future(sql, future),
+            None => future,
+        })
+    }
+
+    fn prepare_with<'e, 'q: 'e>(
+        self,
+        sql: &'q str,
+        parameters: &'e [MySqlTypeInfo],
+    ) -> BoxFuture<'e, Result<MySqlStatement<'q>, sqlx::Error>>
+    where
+        'c: 'e,
+    {
+        match self.connection() {
+            Some(conn) => conn.prepare_with(sql, parameters),
+            None => Box::pin(async { Err(consumed_error()) }),
+        }
+    }
+
+    fn describe<'e, 'q: 'e>(
+        self,
+        sql: &'q str,
+    ) -> BoxFuture<'e, Result<Describe<Self::Database>, sqlx::Error>>
+    where
+        'c: 'e,
+    {
+        match self.connection() {
+            Some(conn) => conn.describe(sql),
+            None => Box::pin(async { Err(consumed_error()) }),
+        }
+    }
+}
+
+/// Hands out the transaction's connection, so generic code written against
+/// `impl Acquire` can run inside the transaction.
+///
+/// Calling [`Acquire::begin`] creates a savepoint within the transaction, which is
+/// released on commit and rolled back to on rollback or drop.
+///
+/// Both methods return an error if the transaction has already been consumed.
+impl<'c, 'tx> Acquire<'c> for &'c mut TransactionContext<'tx> {
+    type Database = MySql;
+
+    type Connection = &'c mut MySqlConnection;
+
+    fn acquire(self) -> BoxFuture<'c, Result<Self::Connection, sqlx::Error>> {
+        let conn = self.connection().ok_or_else(consumed_error);
+        Box::pin(async move { conn })
+    }
+
+    fn begin(self) -> BoxFuture<'c, Result<Transaction<'c, MySql>, sqlx::Error>> {
+        match self.connection() {
+            Some(conn) => Transaction::begin(conn, None),
+            None => Box::pin(async { Err(consumed_error()) }),
+        }
+    }
+}
+
+/// The error sqlx trait methods return when the transaction has already been consumed.
+fn consumed_error() -> sqlx::Error {
+    sqlx::Error::Protocol("transaction has already been consumed".into())
 }
 
 impl<'tx> Drop for TransactionContext<'tx> {
@@ -193,20 +1045,160 @@ impl<'tx> Drop for TransactionContext<'tx> {
     /// This ensures that uncommitted transactions are always rolled back,
     /// preventing accidental commits when errors occur or when the transaction
     /// context goes out of scope.
+    ///
+    /// The drop is reported as a `dropped` outcome, distinct from an explicit
+    /// rollback, and passed to the [`TransactionOptions::on_drop`] callback.
     fn drop(&mut self) {
-        // If tx is Some, it means commit() was not called.
+        // If tx is Some, it means neither commit() nor rollback() was called.
         // SQLx's Transaction automatically rolls back on drop,
-        // so we don't need to do anything here.
+        // so we only need to report it.
+        if self.tx.is_some() {
+            let elapsed = self.elapsed();
+            self.check_limits(elapsed);
+            self.instrumentation
+                .rolled_back(elapsed, RollbackReason::Drop);
+            self.on_drop.report(&DroppedTransaction {
+                label: self.label.as_deref(),
+                elapsed,
+                panicking: std::thread::panicking(),
+            });
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::CommitMarker;
+    use sqlx::mysql::MySqlPoolOptions;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    fn consumed() -> TransactionContext<'static> {
+        TransactionContext {
+            tx: None,
+            commit_check: None,
+            label: None,
+            started_at: Instant::now(),
+            savepoint_depth: 0,
+            instrumentation: Instrumentation::began(&TransactionOptions::default(), None),
+            limits: LimitState::default(),
+            journal: None,
+            registration: None,
+            connection_id: None,
+            trx_id: None,
+            deadlock_pool: None,
+            sql_comment: None,
+            tagged_sql: String::new(),
+            on_drop: DropPolicy::default(),
+        }
+    }
 
     #[test]
     fn test_transaction_context_can_be_created() {
         // This test just ensures the struct can be instantiated
         // Actual database tests require a connection pool
     }
+
+    #[test]
+    fn test_consumed_context_reports_already_consumed() {
+        let mut ctx = consumed();
+        assert!(!ctx.is_active());
+        assert!(matches!(
+            ctx.try_as_executor(),
+            Err(crate::Error::AlreadyConsumed)
+        ));
+        assert!(matches!(
+            ctx.try_into_inner(),
+            Err(crate::Error::AlreadyConsumed)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_queries_on_consumed_context_fail() {
+        let mut ctx = consumed();
+        assert!(sqlx::query("SELECT 1").execute(&mut ctx).await.is_err());
+        assert!(sqlx::query("SELECT 1")
+            .fetch_optional(&mut ctx)
+            .await
+            .is_err());
+        assert!(sqlx::query("SELECT 1").fetch_all(&mut ctx).await.is_err());
+        assert!((&mut ctx).acquire().await.is_err());
+        assert!((&mut ctx).begin().await.is_err());
+    }
+
+    #[test]
+    fn test_errors_carry_the_label() {
+        let mut ctx = consumed();
+        ctx.label = Some("checkout.place_order".into());
+
+        let error = ctx.try_as_executor().unwrap_err();
+        assert_eq!(error.label(), Some("checkout.place_order"));
+        assert!(matches!(
+            error.without_context(),
+            crate::Error::AlreadyConsumed
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_owned_contexts_can_be_spawned() {
+        fn assert_spawnable<T: Send + 'static>(_: &T) {}
+
+        let pool = MySqlPool::connect_lazy("mysql://localhost/test").unwrap();
+        let task = async move {
+            let tx = TransactionContext::begin_owned(&pool).await?;
+            assert_spawnable(&tx);
+            tx.commit().await
+        };
+        assert_spawnable(&task);
+    }
+
+    fn counting_drops(options: TransactionOptions) -> (TransactionOptions, Arc<AtomicUsize>) {
+        let drops = Arc::new(AtomicUsize::new(0));
+        let hook_drops = Arc::clone(&drops);
+        let options = options.panic_on_drop().on_drop(move |_| {
+            hook_drops.fetch_add(1, Ordering::SeqCst);
+        });
+        (options, drops)
+    }
+
+    #[tokio::test]
+    async fn test_failed_begin_is_not_reported_as_dropped() {
+        let pool = MySqlPoolOptions::new()
+            .acquire_timeout(Duration::from_millis(100))
+            .connect_lazy("mysql://localhost:1/test")
+            .unwrap();
+        let (options, drops) = counting_drops(TransactionOptions::new());
+
+        assert!(TransactionContext::begin_with(&pool, options)
+            .await
+            .is_err());
+        assert_eq!(drops.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a MySQL server at DATABASE_URL"]
+    async fn test_failed_set_up_is_not_reported_as_dropped() {
+        let pool = MySqlPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let (options, drops) = counting_drops(
+            TransactionOptions::new().verify_commit(CommitMarker::new("missing_marker_table")),
+        );
+
+        assert!(TransactionContext::begin_with(&pool, options)
+            .await
+            .is_err());
+        assert_eq!(drops.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn test_errors_carry_the_journal() {
+        let mut ctx = consumed();
+        assert!(ctx.try_as_executor().unwrap_err().journal().is_none());
+
+        ctx.journal = Some(Journal::new(8, Instant::now()));
+        let error = ctx.try_as_executor().unwrap_err();
+        assert_eq!(error.journal(), Some(&StatementJournal::default()));
+    }
 }
@@ -0,0 +1,287 @@
+//! Deadlock diagnostics from `SHOW ENGINE INNODB STATUS`.
+//!
+//! Enable them with
+//! [`TransactionOptions::diagnose_deadlocks`](crate::TransactionOptions::diagnose_deadlocks).
+
+use sqlx::{MySqlPool, Row};
+use std::fmt;
+
+/// The `LATEST DETECTED DEADLOCK` section of `SHOW ENGINE INNODB STATUS`, parsed.
+///
+/// InnoDB only keeps the most recent deadlock, so under heavy contention the report
+/// may describe a later deadlock than the one that failed the transaction. Compare
+/// [`DeadlockTransaction::thread_id`] with the transaction's
+/// [`connection_id`](crate::TransactionContext::connection_id) to check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DeadlockReport {
+    /// When InnoDB detected the deadlock, as printed by the server
+    pub detected_at: Option<String>,
+    /// The transactions involved, in the order InnoDB lists them
+    pub transactions: Vec<DeadlockTransaction>,
+    /// The unparsed section without the record dumps, for details not captured in
+    /// the other fields
+    pub raw: String,
+}
+
+/// One transaction involved in a deadlock.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DeadlockTransaction {
+    /// The InnoDB transaction ID
+    pub trx_id: Option<u64>,
+    /// The MySQL thread (connection) ID
+    pub thread_id: Option<u64>,
+    /// The statement the transaction was running
+    pub query: Option<String>,
+    /// Locks the transaction held, one line per lock structure
+    pub holds: Vec<String>,
+    /// Locks the transaction was waiting for
+    pub waiting_for: Vec<String>,
+    /// Whether InnoDB chose this transaction as the victim and rolled it back
+    pub rolled_back: bool,
+}
+
+impl DeadlockReport {
+    /// Returns the transaction InnoDB rolled back, if reported.
+    pub fn victim(&self) -> Option<&DeadlockTransaction> {
+        self.transactions.iter().find(|tx| tx.rolled_back)
+    }
+
+    /// Parses the output of `SHOW ENGINE INNODB STATUS`.
+    ///
+    /// Returns `None` if no deadlock has been detected since the server started.
+    pub fn parse(status: &str) -> Option<DeadlockReport> {
+        let start = status.find("LATEST DETECTED DEADLOCK")?;
+        let section = &status[start..];
+        // Skip the dashes under the heading.
+        let section_lines: Vec<&str> = section
+            .lines()
+            .skip(1)
+            .skip_while(|line| line.starts_with('-'))
+            .take_while(|line| !line.starts_with("-----"))
+            .collect();
+
+        let mut report = DeadlockReport {
+            detected_at: None,
+            transactions: Vec::new(),
+            raw: String::new(),
+        };
+        // Lines kept in `raw`: everything except the record dumps, which contain row data.
+        let mut raw = Vec::with_capacity(section_lines.len());
+
+        let mut current: Option<DeadlockTransaction> = None;
+        let mut block = Block::Transaction;
+        for line in section_lines {
+            if let Some(heading) = line.strip_prefix("*** ") {
+                raw.push(line);
+                if heading.ends_with("TRANSACTION:") {
+                    report.transactions.extend(current.take());
+                    current = Some(DeadlockTransaction::default());
+                    block = Block::Transaction;
+                } else if heading.contains("HOLDS THE LOCK") {
+                    block = Block::Holds;
+                } else if heading.contains("WAITING FOR THIS LOCK") {
+                    block = Block::Waiting;
+                } else if let Some(victim) = heading.strip_prefix("WE ROLL BACK TRANSACTION (") {
+                    report.transactions.extend(current.take());
+                    let number = victim
+                        .split(')')
+                        .next()
+                        .and_then(|n| n.parse::<usize>().ok());
+                    if let Some(tx) =
+                        number.and_then(|n| report.transactions.get_mut(n.checked_sub(1)?))
+                    {
+                        tx.rolled_back = true;
+                    }
+                }
+                continue;
+            }
+
+            if !matches!(block, Block::Holds | Block::Waiting) || is_lock_line(line) {
+                raw.push(line);
+            }
+
+            let Some(tx) = current.as_mut() else {
+                if report.detected_at.is_none() && !line.trim().is_empty() {
+                    report.detected_at = Some(timestamp(line).to_owned());
+                }
+                continue;
+            };
+
+            match block {
+                Block::Transaction => {
+                    if let Some(rest) = line.strip_prefix("TRANSACTION ") {
+                        tx.trx_id = leading_number(rest);
+                    } else if let Some(rest) = line.strip_prefix("MySQL thread id ") {
+                        tx.thread_id = leading_number(rest);
+                    } else if tx.thread_id.is_some() && !is_status_line(line) {
+                        // Everything after the thread line is the statement text,
+                        // which may span several lines.
+                        let query = tx.query.get_or_insert_with(String::new);
+                        if !query.is_empty() {
+                            query.push('\n');
+                        }
+                        query.push_str(line);
+                    }
+                }
+                // Only the lock structure lines are kept; the record dumps below them
+                // contain row data.
+                Block::Holds if is_lock_line(line) => tx.holds.push(line.to_owned()),
+                Block::Waiting if is_lock_line(line) => tx.waiting_for.push(line.to_owned()),
+                Block::Holds | Block::Waiting => {}
+            }
+        }
+        report.transactions.extend(current);
+        report.raw = raw.join("\n");
+
+        Some(report)
+    }
+}
+
+impl fmt::Display for DeadlockReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "deadlock between {} transactions",
+            self.transactions.len()
+        )?;
+        for (i, tx) in self.transactions.iter().enumerate() {
+            write!(f, "\n({}) thread {:?}", i + 1, tx.thread_id)?;
+            if tx.rolled_back {
+                f.write_str(" [rolled back]")?;
+            }
+            if let Some(query) = &tx.query {
+                write!(f, ": {query}")?;
+            }
+            for lock in &tx.holds {
+                write!(f, "\n    holds {lock}")?;
+            }
+            for lock in &tx.waiting_for {
+                write!(f, "\n    waits for {lock}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Block {
+    Transaction,
+    Holds,
+    Waiting,
+}
+
+/// Fetches the latest deadlock report on a fresh pool connection.
+///
+/// Returns `None` if the status cannot be read (it requires the `PROCESS` privilege)
+/// or contains no deadlock.
+pub(crate) async fn fetch(pool: &MySqlPool) -> Option<DeadlockReport> {
+    let row = sqlx::query("SHOW ENGINE INNODB STATUS")
+        .fetch_one(pool)
+        .await
+        .ok()?;
+    let status: String = row.try_get("Status").ok()?;
+    DeadlockReport::parse(&status)
+}
+
+fn leading_number(s: &str) -> Option<u64> {
+    let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    s[..end].parse().ok()
+}
+
+fn timestamp(line: &str) -> &str {
+    // The timestamp line ends with the hex ID of the OS thread that printed it.
+    match line.rsplit_once(' ') {
+        Some((time, thread)) if thread.starts_with("0x") => time,
+        _ => line,
+    }
+    .trim()
+}
+
+fn is_status_line(line: &str) -> bool {
+    ["mysql tables in use", "LOCK WAIT", "TRANSACTION "]
+        .iter()
+        .any(|prefix| line.starts_with(prefix))
+}
+
+fn is_lock_line(line: &str) -> bool {
+    line.starts_with("RECORD LOCKS") || line.starts_with("TABLE LOCK")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STATUS: &str = "\
+=====================================
+2024-05-01 10:00:05 0x7f1c INNODB MONITOR OUTPUT
+=====================================
+------------------------
+LATEST DETECTED DEADLOCK
+------------------------
+2024-05-01 10:00:01 0x7f1c2c0d9700
+*** (1) TRANSACTION:
+TRANSACTION 421, ACTIVE 4 sec starting index read
+mysql tables in use 1, locked 1
+LOCK WAIT 3 lock struct(s), heap size 1128, 2 row lock(s)
+MySQL thread id 10, OS thread handle 139, query id 57 localhost app updating
+UPDATE accounts SET balance = balance - 10 WHERE id = 2
+*** (1) HOLDS THE LOCK(S):
+RECORD LOCKS space id 2 page no 4 n bits 72 index PRIMARY of table `shop`.`accounts` trx id 421 lock_mode X locks rec but not gap
+Record lock, heap no 2 PHYSICAL RECORD: n_fields 4; compact format; info bits 0
+ 0: len 4; hex 80000001; asc     ;;
+*** (1) WAITING FOR THIS LOCK TO BE GRANTED:
+RECORD LOCKS space id 2 page no 4 n bits 72 index PRIMARY of table `shop`.`accounts` trx id 421 lock_mode X locks rec but not gap waiting
+Record lock, heap no 3 PHYSICAL RECORD: n_fields 4; compact format; info bits 0
+*** (2) TRANSACTION:
+TRANSACTION 422, ACTIVE 3 sec starting index read
+mysql tables in use 1, locked 1
+MySQL thread id 11, OS thread handle 140, query id 58 localhost app updating
+UPDATE accounts
+SET balance = balance + 10 WHERE id = 1
+*** (2) HOLDS THE LOCK(S):
+RECORD LOCKS space id 2 page no 4 n bits 72 index PRIMARY of table `shop`.`accounts` trx id 422 lock_mode X locks rec but not gap
+*** (2) WAITING FOR THIS LOCK TO BE GRANTED:
+RECORD LOCKS space id 2 page no 4 n bits 72 index PRIMARY of table `shop`.`accounts` trx id 422 lock_mode X locks rec but not gap waiting
+*** WE ROLL BACK TRANSACTION (2)
+------------
+TRANSACTIONS
+------------
+Trx id counter 430
+";
+
+    #[test]
+    fn test_parse_latest_deadlock() {
+        let report = DeadlockReport::parse(STATUS).unwrap();
+        assert_eq!(report.detected_at.as_deref(), Some("2024-05-01 10:00:01"));
+        assert_eq!(report.transactions.len(), 2);
+
+        let first = &report.transactions[0];
+        assert_eq!(first.trx_id, Some(421));
+        assert_eq!(first.thread_id, Some(10));
+        assert_eq!(
+            first.query.as_deref(),
+            Some("UPDATE accounts SET balance = balance - 10 WHERE id = 2")
+        );
+        assert_eq!(first.holds.len(), 1);
+        assert_eq!(first.waiting_for.len(), 1);
+        assert!(!first.rolled_back);
+
+        let victim = report.victim().unwrap();
+        assert_eq!(victim.thread_id, Some(11));
+        assert_eq!(
+            victim.query.as_deref(),
+            Some("UPDATE accounts\nSET balance = balance + 10 WHERE id = 1")
+        );
+        assert!(!report.raw.contains("Trx id counter"));
+        assert!(report.raw.contains("WE ROLL BACK TRANSACTION (2)"));
+        assert!(!report.raw.contains("PHYSICAL RECORD"));
+        assert!(!report.raw.contains("hex 80000001"));
+    }
+
+    #[test]
+    fn test_parse_without_deadlock() {
+        assert_eq!(DeadlockReport::parse("TRANSACTIONS\n------------\n"), None);
+    }
+}
@@ -0,0 +1,122 @@
+//! Reporting of transactions dropped without `commit()` or `rollback()`.
+//!
+//! Configure it with [`TransactionOptions::on_drop`](crate::TransactionOptions::on_drop)
+//! and [`TransactionOptions::panic_on_drop`](crate::TransactionOptions::panic_on_drop).
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Details passed to a drop callback.
+///
+/// A context dropped while its transaction is still open usually indicates a bug, such
+/// as an early `return` or `?` before `commit()`, or a cancelled future. SQLx rolls the
+/// transaction back; this reports that it happened.
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub struct DroppedTransaction<'a> {
+    /// The transaction's label, if any
+    pub label: Option<&'a str>,
+    /// How long the transaction was open
+    pub elapsed: Duration,
+    /// Whether the context was dropped while the thread was unwinding from a panic
+    pub panicking: bool,
+}
+
+type DropHook = Arc<dyn Fn(&DroppedTransaction<'_>) + Send + Sync>;
+
+/// What to do when a context is dropped with its transaction still open.
+#[derive(Clone, Default)]
+pub(crate) struct DropPolicy {
+    pub(crate) hook: Option<DropHook>,
+    pub(crate) panic: bool,
+}
+
+impl DropPolicy {
+    /// Reports a dropped transaction to the callback, then panics if configured to.
+    ///
+    /// The panic only happens in debug builds, and never while already unwinding,
+    /// which would abort the process.
+    pub(crate) fn report(&self, dropped: &DroppedTransaction<'_>) {
+        if let Some(hook) = &self.hook {
+            hook(dropped);
+        }
+
+        if cfg!(debug_assertions) && self.panic && !dropped.panicking {
+            match dropped.label {
+                Some(label) => panic!(
+                    "transaction `{label}` dropped without commit() or rollback() after {:?}",
+                    dropped.elapsed
+                ),
+                None => panic!(
+                    "transaction dropped without commit() or rollback() after {:?}",
+                    dropped.elapsed
+                ),
+            }
+        }
+    }
+}
+
+impl fmt::Debug for DropPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DropPolicy")
+            .field("hook", &self.hook.is_some())
+            .field("panic", &self.panic)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    fn dropped(panicking: bool) -> DroppedTransaction<'static> {
+        DroppedTransaction {
+            label: Some("checkout.place_order"),
+            elapsed: Duration::from_millis(3),
+            panicking,
+        }
+    }
+
+    #[test]
+    fn test_hook_receives_drop_details() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let hook_seen = Arc::clone(&seen);
+        let policy = DropPolicy {
+            hook: Some(Arc::new(move |tx: &DroppedTransaction<'_>| {
+                hook_seen
+                    .lock()
+                    .unwrap()
+                    .push((tx.label.map(str::to_owned), tx.panicking));
+            })),
+            panic: false,
+        };
+
+        policy.report(&dropped(true));
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![(Some("checkout.place_order".to_owned()), true)]
+        );
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "transaction `checkout.place_order` dropped without commit()")]
+    fn test_panic_on_drop() {
+        let policy = DropPolicy {
+            hook: None,
+            panic: true,
+        };
+        policy.report(&dropped(false));
+    }
+
+    #[test]
+    fn test_no_panic_while_unwinding() {
+        let policy = DropPolicy {
+            hook: None,
+            panic: true,
+        };
+        policy.report(&dropped(true));
+    }
+}
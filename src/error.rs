@@ -1,3 +1,10 @@
+use crate::deadlock::DeadlockReport;
+use crate::journal::StatementJournal;
+use crate::verification::CommitOutcome;
+use std::borrow::Cow;
+use std::fmt;
+use std::time::Duration;
+
 /// Error types for transaction management
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -9,10 +16,273 @@ pub enum Error {
     #[error("Transaction has already been consumed")]
     AlreadyConsumed,
 
+    /// Commit failed without a response from the server and could not be confirmed
+    #[error("Commit failed ({outcome}): {source}")]
+    AmbiguousCommit {
+        /// Result of checking the commit marker after the failure
+        outcome: CommitOutcome,
+        /// The error returned by `COMMIT`
+        #[source]
+        source: sqlx::Error,
+    },
+
+    /// The transaction stayed open past its hard limit and was rolled back instead of committed
+    #[error("Transaction was open for {elapsed:?}, exceeding the limit of {limit:?}; rolled back")]
+    TimeLimitExceeded {
+        /// How long the transaction had been open
+        elapsed: Duration,
+        /// The configured limit
+        limit: Duration,
+    },
+
+    /// An ambient transaction was requested outside of an `ambient` scope
+    ///
+    /// Only returned with the `ambient` feature, but always declared so that enabling the
+    /// feature does not change the set of variants.
+    #[error("No ambient transaction is active in this task")]
+    NoAmbientTransaction,
+
+    /// The work succeeded, but an owner had marked the transaction rollback-only, so it
+    /// was rolled back and the value discarded
+    ///
+    /// Only returned with the `shared` feature, by `with_transaction_owned`.
+    #[error("Transaction was marked rollback-only and has been rolled back")]
+    RolledBack,
+
     /// Generic error message for compatibility
     #[error("{0}")]
     Other(String),
+
+    /// An error annotated with details about the transaction it occurred in
+    #[error("{context}: {source}")]
+    Transaction {
+        /// Details about the transaction
+        context: Box<ErrorContext>,
+        /// The underlying error
+        #[source]
+        source: Box<Error>,
+    },
+}
+
+/// Details about the transaction an [`Error`] occurred in.
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct ErrorContext {
+    /// The transaction's label, see [`TransactionOptions::label`](crate::TransactionOptions::label)
+    pub label: Option<Cow<'static, str>>,
+    /// The statements run before the error, see [`TransactionOptions::journal`](crate::TransactionOptions::journal)
+    pub journal: Option<StatementJournal>,
+    /// The MySQL `CONNECTION_ID()` of the transaction's connection, if it was fetched
+    pub connection_id: Option<u64>,
+    /// The InnoDB transaction ID, if it was fetched
+    pub trx_id: Option<u64>,
+    /// The deadlock that failed the transaction, see [`TransactionOptions::diagnose_deadlocks`](crate::TransactionOptions::diagnose_deadlocks)
+    pub deadlock: Option<DeadlockReport>,
+}
+
+impl ErrorContext {
+    fn is_empty(&self) -> bool {
+        self.label.is_none()
+            && self.journal.is_none()
+            && self.connection_id.is_none()
+            && self.trx_id.is_none()
+            && self.deadlock.is_none()
+    }
+
+    /// Fills fields of `self` that are unset with the values from `other`.
+    fn merge(&mut self, other: ErrorContext) {
+        if self.label.is_none() {
+            self.label = other.label;
+        }
+        if self.journal.is_none() {
+            self.journal = other.journal;
+        }
+        self.connection_id = self.connection_id.or(other.connection_id);
+        self.trx_id = self.trx_id.or(other.trx_id);
+        if self.deadlock.is_none() {
+            self.deadlock = other.deadlock;
+        }
+    }
+}
+
+impl fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.label {
+            Some(label) => write!(f, "transaction `{label}`")?,
+            None => f.write_str("transaction")?,
+        }
+        match (self.connection_id, self.trx_id) {
+            (Some(connection_id), Some(trx_id)) => {
+                write!(f, " (connection {connection_id}, trx {trx_id})")
+            }
+            (Some(connection_id), None) => write!(f, " (connection {connection_id})"),
+            (None, Some(trx_id)) => write!(f, " (trx {trx_id})"),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+impl Error {
+    /// Annotates the error with transaction details.
+    ///
+    /// Details already present on the error are kept; an empty context leaves the
+    /// error unchanged.
+    pub(crate) fn with_context(self, context: ErrorContext) -> Error {
+        if context.is_empty() {
+            return self;
+        }
+
+        match self {
+            Error::Transaction {
+                context: mut existing,
+                source,
+            } => {
+                existing.merge(context);
+                Error::Transaction {
+                    context: existing,
+                    source,
+                }
+            }
+            error => Error::Transaction {
+                context: Box::new(context),
+                source: Box::new(error),
+            },
+        }
+    }
+
+    /// Returns the transaction details attached to this error, if any.
+    pub fn context(&self) -> Option<&ErrorContext> {
+        match self {
+            Error::Transaction { context, .. } => Some(context),
+            _ => None,
+        }
+    }
+
+    /// Returns the label of the transaction this error occurred in, if any.
+    pub fn label(&self) -> Option<&str> {
+        self.context()?.label.as_deref()
+    }
+
+    /// Returns the statements run in the transaction before this error, if the
+    /// journal was enabled.
+    pub fn journal(&self) -> Option<&StatementJournal> {
+        self.context()?.journal.as_ref()
+    }
+
+    /// Returns the deadlock report attached to this error, if deadlock diagnostics
+    /// were enabled and the report could be fetched.
+    pub fn deadlock_report(&self) -> Option<&DeadlockReport> {
+        self.context()?.deadlock.as_ref()
+    }
+
+    /// Returns the error without its transaction details.
+    ///
+    /// Match on this instead of the error itself to handle labeled and unlabeled
+    /// transactions alike.
+    pub fn without_context(&self) -> &Error {
+        match self {
+            Error::Transaction { source, .. } => source.without_context(),
+            error => error,
+        }
+    }
+
+    /// Returns the MySQL server error number, if this is a server-reported error.
+    pub fn mysql_error_number(&self) -> Option<u16> {
+        match self.without_context() {
+            Error::Database(e) => crate::retry::RetryableError::mysql_error_number(e),
+            _ => None,
+        }
+    }
+
+    /// Returns a short, stable name for the kind of error, suitable for telemetry.
+    pub fn kind(&self) -> &'static str {
+        match self.without_context() {
+            _ if self.is_deadlock() => "deadlock",
+            _ if self.is_lock_wait_timeout() => "lock_wait_timeout",
+            Error::Database(_) => "database",
+            Error::AlreadyConsumed => "already_consumed",
+            Error::AmbiguousCommit { .. } => "ambiguous_commit",
+            Error::TimeLimitExceeded { .. } => "time_limit_exceeded",
+            Error::NoAmbientTransaction => "no_ambient_transaction",
+            Error::RolledBack => "rolled_back",
+            Error::Other(_) => "other",
+            Error::Transaction { source, .. } => source.kind(),
+        }
+    }
+
+    /// Returns `true` if the transaction was chosen as a deadlock victim (error 1213).
+    pub fn is_deadlock(&self) -> bool {
+        self.mysql_error_number() == Some(ER_LOCK_DEADLOCK)
+    }
+
+    /// Returns `true` if a lock wait timed out (error 1205).
+    pub fn is_lock_wait_timeout(&self) -> bool {
+        self.mysql_error_number() == Some(ER_LOCK_WAIT_TIMEOUT)
+    }
 }
 
+pub(crate) const ER_LOCK_WAIT_TIMEOUT: u16 = 1205;
+pub(crate) const ER_LOCK_DEADLOCK: u16 = 1213;
+
 /// Result type alias for transaction operations
 pub type Result<T> = std::result::Result<T, Error>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_kinds() {
+        assert_eq!(Error::Database(sqlx::Error::RowNotFound).kind(), "database");
+        assert_eq!(Error::AlreadyConsumed.kind(), "already_consumed");
+        assert_eq!(Error::RolledBack.kind(), "rolled_back");
+        assert_eq!(Error::Other("boom".into()).kind(), "other");
+    }
+
+    fn labeled(label: &'static str) -> ErrorContext {
+        ErrorContext {
+            label: Some(label.into()),
+            ..ErrorContext::default()
+        }
+    }
+
+    #[test]
+    fn test_context_display_includes_server_ids() {
+        let error = Error::AlreadyConsumed.with_context(ErrorContext {
+            connection_id: Some(42),
+            trx_id: Some(1234),
+            ..labeled("checkout.place_order")
+        });
+        assert_eq!(
+            error.to_string(),
+            "transaction `checkout.place_order` (connection 42, trx 1234): \
+             Transaction has already been consumed"
+        );
+    }
+
+    #[test]
+    fn test_context_is_attached_once() {
+        let error = Error::AlreadyConsumed
+            .with_context(labeled("checkout.place_order"))
+            .with_context(labeled("outer"));
+
+        assert_eq!(error.label(), Some("checkout.place_order"));
+        assert_eq!(error.kind(), "already_consumed");
+        assert!(matches!(error.without_context(), Error::AlreadyConsumed));
+        assert!(matches!(
+            &error,
+            Error::Transaction { source, .. } if matches!(**source, Error::AlreadyConsumed)
+        ));
+        assert_eq!(
+            error.to_string(),
+            "transaction `checkout.place_order`: Transaction has already been consumed"
+        );
+    }
+
+    #[test]
+    fn test_empty_context_leaves_error_unchanged() {
+        let error = Error::AlreadyConsumed.with_context(ErrorContext::default());
+        assert!(matches!(error, Error::AlreadyConsumed));
+        assert_eq!(error.label(), None);
+    }
+}
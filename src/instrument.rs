@@ -0,0 +1,603 @@
+//! Lifecycle hooks for observability features.
+//!
+//! `TransactionContext` and the executor helpers report lifecycle events here. Each
+//! hook compiles to nothing unless the corresponding feature is enabled.
+
+use crate::options::TransactionOptions;
+use futures_core::future::BoxFuture;
+use futures_core::stream::BoxStream;
+use sqlx::MySqlPool;
+use std::future::Future;
+use std::time::Duration;
+
+#[cfg(feature = "metrics")]
+const TRANSACTIONS_TOTAL: &str = "sqlx_transactions_total";
+#[cfg(feature = "metrics")]
+const TRANSACTION_DURATION_SECONDS: &str = "sqlx_transaction_duration_seconds";
+#[cfg(feature = "metrics")]
+const TRANSACTION_RETRIES_TOTAL: &str = "sqlx_transaction_retries_total";
+
+/// Why a transaction was rolled back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RollbackReason {
+    /// `rollback()` was called.
+    Explicit,
+    /// The context was dropped while the transaction was still open.
+    Drop,
+}
+
+impl RollbackReason {
+    fn as_str(self) -> &'static str {
+        match self {
+            RollbackReason::Explicit => "rolled_back",
+            RollbackReason::Drop => "dropped",
+        }
+    }
+}
+
+/// A savepoint statement issued by `with_nested_transaction`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SavepointOp {
+    Create,
+    Release,
+    RollbackTo,
+}
+
+impl SavepointOp {
+    #[cfg_attr(not(feature = "tracing"), allow(dead_code))]
+    fn as_str(self) -> &'static str {
+        match self {
+            SavepointOp::Create => "savepoint",
+            SavepointOp::Release => "release_savepoint",
+            SavepointOp::RollbackTo => "rollback_to_savepoint",
+        }
+    }
+}
+
+/// Per-transaction instrumentation state, owned by a `TransactionContext`.
+pub(crate) struct Instrumentation {
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+    #[cfg(feature = "opentelemetry")]
+    db_name: Option<String>,
+    #[cfg(feature = "metrics")]
+    name: metrics::SharedString,
+}
+
+impl Instrumentation {
+    /// Starts instrumenting a transaction that has just begun on a connection from `pool`.
+    pub(crate) fn began(options: &TransactionOptions, pool: Option<&MySqlPool>) -> Self {
+        #[cfg(feature = "tracing")]
+        let db_name =
+            pool.and_then(|pool| pool.connect_options().get_database().map(str::to_owned));
+
+        #[cfg(feature = "tracing")]
+        let span = {
+            let span = tracing::info_span!(
+                "transaction",
+                label = options.label.as_deref().unwrap_or_default(),
+                read_only = options.read_only,
+                isolation_level = options.isolation_level.map(|l| l.as_sql()),
+                db.system = "mysql",
+                db.name = db_name.as_deref(),
+                outcome = tracing::field::Empty,
+                duration_ms = tracing::field::Empty,
+                error.kind = tracing::field::Empty,
+                db.mysql.connection_id = tracing::field::Empty,
+                db.mysql.trx_id = tracing::field::Empty,
+                otel.name = tracing::field::Empty,
+                otel.kind = tracing::field::Empty,
+                otel.status_code = tracing::field::Empty,
+            );
+            #[cfg(feature = "opentelemetry")]
+            {
+                span.record(
+                    "otel.name",
+                    options.label.as_deref().unwrap_or("transaction"),
+                );
+                span.record("otel.kind", "client");
+            }
+            span.in_scope(|| tracing::debug!("transaction started"));
+            span
+        };
+
+        let _ = (options, pool);
+        Self {
+            #[cfg(feature = "tracing")]
+            span,
+            #[cfg(feature = "opentelemetry")]
+            db_name,
+            #[cfg(feature = "metrics")]
+            name: metric_name(options),
+        }
+    }
+
+    /// Reports a transaction that could not be started.
+    pub(crate) fn begin_failed(options: &TransactionOptions, error: &crate::Error) {
+        #[cfg(feature = "tracing")]
+        tracing::error!(
+            label = options.label.as_deref().unwrap_or_default(),
+            error.kind = error.kind(),
+            error = %error,
+            "transaction failed to start"
+        );
+
+        #[cfg(feature = "metrics")]
+        metrics::counter!(
+            TRANSACTIONS_TOTAL,
+            "transaction" => metric_name(options),
+            "outcome" => "begin_failed"
+        )
+        .increment(1);
+
+        let _ = (options, error);
+    }
+
+    /// Reports that a failed attempt is about to be retried.
+    pub(crate) fn retrying(options: &TransactionOptions, attempt: u32, error: &crate::Error) {
+        #[cfg(feature = "tracing")]
+        tracing::info!(
+            label = options.label.as_deref().unwrap_or_default(),
+            attempt,
+            error.kind = error.kind(),
+            error = %error,
+            "retrying transaction"
+        );
+
+        #[cfg(feature = "metrics")]
+        metrics::counter!(
+            TRANSACTION_RETRIES_TOTAL,
+            "transaction" => metric_name(options),
+            "error_kind" => error.kind()
+        )
+        .increment(1);
+
+        let _ = (options, attempt, error);
+    }
+
+    /// Records the error that caused the transaction to be abandoned.
+    pub(crate) fn failed(&self, error: &crate::Error) {
+        #[cfg(feature = "tracing")]
+        self.span.record("error.kind", error.kind());
+
+        #[cfg(feature = "opentelemetry")]
+        self.span.record("otel.status_code", "error");
+
+        #[cfg(not(feature = "tracing"))]
+        let _ = error;
+    }
+
+    pub(crate) fn committed(&self, elapsed: Duration) {
+        self.finish("committed", elapsed);
+
+        #[cfg(feature = "opentelemetry")]
+        self.span.record("otel.status_code", "ok");
+
+        #[cfg(feature = "tracing")]
+        self.span
+            .in_scope(|| tracing::debug!(duration_ms = millis(elapsed), "transaction committed"));
+    }
+
+    pub(crate) fn commit_failed(&self, elapsed: Duration, error: &crate::Error) {
+        self.finish("commit_failed", elapsed);
+
+        #[cfg(feature = "opentelemetry")]
+        self.span.record("otel.status_code", "error");
+
+        #[cfg(feature = "tracing")]
+        {
+            self.span.record("error.kind", error.kind());
+            self.span.in_scope(|| {
+                tracing::error!(
+                    duration_ms = millis(elapsed),
+                    error.kind = error.kind(),
+                    error = %error,
+                    "transaction commit failed"
+                )
+            });
+        }
+
+        let _ = error;
+    }
+
+    pub(crate) fn rolled_back(&self, elapsed: Duration, reason: RollbackReason) {
+        self.finish(reason.as_str(), elapsed);
+
+        #[cfg(feature = "tracing")]
+        {
+            self.span.in_scope(|| match reason {
+                RollbackReason::Explicit => {
+                    tracing::debug!(duration_ms = millis(elapsed), "transaction rolled back")
+                }
+                RollbackReason::Drop => tracing::warn!(
+                    duration_ms = millis(elapsed),
+                    panicking = std::thread::panicking(),
+                    "transaction dropped without commit or rollback, rolling back"
+                ),
+            });
+        }
+    }
+
+    pub(crate) fn rollback_failed(&self, elapsed: Duration, error: &crate::Error) {
+        self.finish("rollback_failed", elapsed);
+
+        #[cfg(feature = "opentelemetry")]
+        self.span.record("otel.status_code", "error");
+
+        #[cfg(feature = "tracing")]
+        {
+            self.span.in_scope(|| {
+                tracing::warn!(
+                    duration_ms = millis(elapsed),
+                    error.kind = error.kind(),
+                    error = %error,
+                    "transaction rollback failed"
+                )
+            });
+        }
+
+        let _ = error;
+    }
+
+    /// Reports a transaction that has stayed open past its warning threshold.
+    pub(crate) fn long_open(&self, elapsed: Duration, threshold: Duration) {
+        #[cfg(feature = "tracing")]
+        self.span.in_scope(|| {
+            tracing::warn!(
+                elapsed_ms = millis(elapsed),
+                threshold_ms = millis(threshold),
+                "transaction open longer than threshold"
+            )
+        });
+
+        #[cfg(not(feature = "tracing"))]
+        let _ = (elapsed, threshold);
+    }
+
+    /// Records the server-side identifiers of the transaction once they are known.
+    pub(crate) fn server_ids(&self, connection_id: Option<u64>, trx_id: Option<u64>) {
+        #[cfg(feature = "tracing")]
+        {
+            if let Some(connection_id) = connection_id {
+                self.span.record("db.mysql.connection_id", connection_id);
+            }
+            if let Some(trx_id) = trx_id {
+                self.span.record("db.mysql.trx_id", trx_id);
+            }
+        }
+
+        #[cfg(not(feature = "tracing"))]
+        let _ = (connection_id, trx_id);
+    }
+
+    /// Creates the span for a statement issued through the transaction.
+    pub(crate) fn statement(&self, sql: &str) -> StatementSpan {
+        #[cfg(feature = "opentelemetry")]
+        {
+            let operation = operation(sql);
+            let name = match &self.db_name {
+                Some(db_name) => format!("{operation} {db_name}"),
+                None => operation.clone(),
+            };
+            let span = tracing::info_span!(
+                parent: &self.span,
+                "statement",
+                otel.name = name,
+                otel.kind = "client",
+                otel.status_code = tracing::field::Empty,
+                db.system = "mysql",
+                db.name = self.db_name.as_deref(),
+                db.operation = operation,
+                db.statement = sql,
+            );
+            StatementSpan { span }
+        }
+
+        #[cfg(not(feature = "opentelemetry"))]
+        {
+            let _ = sql;
+            StatementSpan {}
+        }
+    }
+
+    /// Creates the span that a nested transaction at `depth` runs in.
+    pub(crate) fn savepoint_span(&self, depth: usize) -> SavepointSpan {
+        #[cfg(feature = "tracing")]
+        {
+            let span = self.span.in_scope(|| {
+                tracing::debug_span!(
+                    "savepoint",
+                    depth,
+                    outcome = tracing::field::Empty,
+                    duration_ms = tracing::field::Empty,
+                    error.kind = tracing::field::Empty,
+                )
+            });
+            SavepointSpan { span }
+        }
+
+        #[cfg(not(feature = "tracing"))]
+        {
+            let _ = depth;
+            SavepointSpan {}
+        }
+    }
+
+    /// Records how the transaction ended and how long it was open.
+    fn finish(&self, outcome: &'static str, elapsed: Duration) {
+        #[cfg(feature = "tracing")]
+        {
+            self.span.record("outcome", outcome);
+            self.span.record("duration_ms", millis(elapsed));
+        }
+
+        #[cfg(feature = "metrics")]
+        {
+            metrics::counter!(
+                TRANSACTIONS_TOTAL,
+                "transaction" => self.name.clone(),
+                "outcome" => outcome
+            )
+            .increment(1);
+            metrics::histogram!(
+                TRANSACTION_DURATION_SECONDS,
+                "transaction" => self.name.clone(),
+                "outcome" => outcome
+            )
+            .record(elapsed.as_secs_f64());
+        }
+
+        let _ = (outcome, elapsed);
+    }
+}
+
+/// Child span covering one statement run through the transaction.
+pub(crate) struct StatementSpan {
+    #[cfg(feature = "opentelemetry")]
+    span: tracing::Span,
+}
+
+impl StatementSpan {
+    /// Runs the rows streamed by `stream` inside the span.
+    pub(crate) fn stream<'e, T: 'e, E: 'e>(
+        self,
+        stream: BoxStream<'e, Result<T, E>>,
+    ) -> BoxStream<'e, Result<T, E>> {
+        #[cfg(feature = "opentelemetry")]
+        {
+            Box::pin(otel::InstrumentedStream {
+                inner: stream,
+                span: self.span,
+                failed: false,
+            })
+        }
+
+        #[cfg(not(feature = "opentelemetry"))]
+        stream
+    }
+
+    /// Runs `future` inside the span.
+    pub(crate) fn future<'e, T: 'e, E: 'e>(
+        self,
+        future: BoxFuture<'e, Result<T, E>>,
+    ) -> BoxFuture<'e, Result<T, E>> {
+        #[cfg(feature = "opentelemetry")]
+        {
+            use tracing::Instrument;
+            let span = self.span;
+            Box::pin(
+                async move {
+                    let result = future.await;
+                    otel::record_status(&tracing::Span::current(), result.is_ok());
+                    result
+                }
+                .instrument(span),
+            )
+        }
+
+        #[cfg(not(feature = "opentelemetry"))]
+        future
+    }
+}
+
+/// Span covering one nested transaction and its savepoint statements.
+pub(crate) struct SavepointSpan {
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+}
+
+impl SavepointSpan {
+    /// Runs `future` inside the span.
+    pub(crate) async fn instrument<F: Future>(&self, future: F) -> F::Output {
+        #[cfg(feature = "tracing")]
+        {
+            use tracing::Instrument;
+            future.instrument(self.span.clone()).await
+        }
+
+        #[cfg(not(feature = "tracing"))]
+        future.await
+    }
+
+    /// Reports the result of a savepoint statement.
+    pub(crate) fn op(&self, op: SavepointOp, result: Result<(), &crate::Error>) {
+        #[cfg(feature = "tracing")]
+        self.span.in_scope(|| match result {
+            Ok(()) => tracing::debug!(op = op.as_str(), "savepoint statement succeeded"),
+            Err(error) => tracing::warn!(
+                op = op.as_str(),
+                error.kind = error.kind(),
+                error = %error,
+                "savepoint statement failed"
+            ),
+        });
+
+        #[cfg(not(feature = "tracing"))]
+        let _ = (op, result);
+    }
+
+    /// Records how the nested transaction ended.
+    pub(crate) fn finish(&self, elapsed: Duration, error: Option<&crate::Error>) {
+        #[cfg(feature = "tracing")]
+        {
+            self.span.record("duration_ms", millis(elapsed));
+            match error {
+                None => self.span.record("outcome", "released"),
+                Some(error) => self
+                    .span
+                    .record("outcome", "rolled_back")
+                    .record("error.kind", error.kind()),
+            };
+        }
+
+        #[cfg(not(feature = "tracing"))]
+        let _ = (elapsed, error);
+    }
+}
+
+#[cfg(feature = "tracing")]
+fn millis(elapsed: Duration) -> f64 {
+    elapsed.as_secs_f64() * 1000.0
+}
+
+/// Returns the metric label value naming the transaction.
+#[cfg(feature = "metrics")]
+fn metric_name(options: &TransactionOptions) -> metrics::SharedString {
+    match &options.label {
+        Some(std::borrow::Cow::Borrowed(label)) => metrics::SharedString::const_str(label),
+        Some(std::borrow::Cow::Owned(label)) => metrics::SharedString::from_owned(label.clone()),
+        None => metrics::SharedString::const_str("unnamed"),
+    }
+}
+
+#[cfg(feature = "opentelemetry")]
+use otel::operation;
+
+#[cfg(feature = "opentelemetry")]
+mod otel {
+    use futures_core::stream::{BoxStream, Stream};
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    /// Returns the operation name (the leading SQL keyword) of a statement.
+    pub(super) fn operation(sql: &str) -> String {
+        let mut rest = sql.trim_start();
+        // Skip leading `/* ... */` comments, such as query tags.
+        while let Some(comment) = rest.strip_prefix("/*") {
+            rest = comment
+                .split_once("*/")
+                .map_or("", |(_, after)| after)
+                .trim_start();
+        }
+        rest.split(|c: char| c.is_whitespace() || c == '(' || c == ';')
+            .next()
+            .unwrap_or_default()
+            .to_ascii_uppercase()
+    }
+
+    /// Records the OpenTelemetry status of a finished operation.
+    pub(super) fn record_status(span: &tracing::Span, ok: bool) {
+        span.record("otel.status_code", if ok { "ok" } else { "error" });
+    }
+
+    /// Enters `span` whenever the wrapped row stream is polled.
+    pub(super) struct InstrumentedStream<'e, T, E> {
+        pub(super) inner: BoxStream<'e, Result<T, E>>,
+        pub(super) span: tracing::Span,
+        pub(super) failed: bool,
+    }
+
+    impl<T, E> Stream for InstrumentedStream<'_, T, E> {
+        type Item = Result<T, E>;
+
+        fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            let this = &mut *self;
+            let _enter = this.span.enter();
+            let poll = this.inner.as_mut().poll_next(cx);
+            match &poll {
+                Poll::Ready(Some(Err(_))) => {
+                    this.failed = true;
+                    record_status(&this.span, false);
+                }
+                Poll::Ready(None) if !this.failed => record_status(&this.span, true),
+                _ => {}
+            }
+            poll
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_operation_is_leading_keyword() {
+            assert_eq!(operation("select * from users"), "SELECT");
+            assert_eq!(operation("  INSERT INTO users (name) VALUES (?)"), "INSERT");
+            assert_eq!(
+                operation("/* app='api' */ UPDATE users SET name = ?"),
+                "UPDATE"
+            );
+            assert_eq!(operation("COMMIT"), "COMMIT");
+            assert_eq!(operation(""), "");
+        }
+    }
+}
+
+#[cfg(all(test, feature = "metrics"))]
+mod tests {
+    use super::*;
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+    use metrics_util::MetricKind;
+
+    fn labels(key: &metrics::Key) -> Vec<(String, String)> {
+        key.labels()
+            .map(|l| (l.key().to_owned(), l.value().to_owned()))
+            .collect()
+    }
+
+    #[test]
+    fn test_metrics_are_labeled_by_transaction_name() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+
+        metrics::with_local_recorder(&recorder, || {
+            let options = TransactionOptions::new().label("checkout.place_order");
+            Instrumentation::began(&options, None).committed(Duration::from_millis(5));
+            Instrumentation::began(&options, None)
+                .rolled_back(Duration::from_millis(1), RollbackReason::Drop);
+            Instrumentation::retrying(&options, 1, &crate::Error::AlreadyConsumed);
+        });
+
+        let snapshot = snapshotter.snapshot().into_vec();
+        let find = |kind: MetricKind, name: &str, outcome: Option<&str>| {
+            snapshot
+                .iter()
+                .find(|(key, _, _, _)| {
+                    let labels = labels(key.key());
+                    key.kind() == kind
+                        && key.key().name() == name
+                        && labels.contains(&("transaction".into(), "checkout.place_order".into()))
+                        && outcome.is_none_or(|o| labels.contains(&("outcome".into(), o.into())))
+                })
+                .map(|(_, _, _, value)| value)
+        };
+
+        assert_eq!(
+            find(MetricKind::Counter, TRANSACTIONS_TOTAL, Some("committed")),
+            Some(&DebugValue::Counter(1))
+        );
+        assert_eq!(
+            find(MetricKind::Counter, TRANSACTIONS_TOTAL, Some("dropped")),
+            Some(&DebugValue::Counter(1))
+        );
+        assert_eq!(
+            find(MetricKind::Counter, TRANSACTION_RETRIES_TOTAL, None),
+            Some(&DebugValue::Counter(1))
+        );
+        assert!(matches!(
+            find(MetricKind::Histogram, TRANSACTION_DURATION_SECONDS, Some("committed")),
+            Some(DebugValue::Histogram(values)) if values.len() == 1
+        ));
+    }
+}
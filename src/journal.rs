@@ -0,0 +1,256 @@
+//! Per-transaction statement journal for debugging failed transactions.
+//!
+//! Enable it with [`TransactionOptions::journal`](crate::TransactionOptions::journal).
+
+use futures_core::future::BoxFuture;
+use futures_core::stream::{BoxStream, Stream};
+use sqlx::mysql::MySqlQueryResult;
+use sqlx::Either;
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+/// A statement executed through a [`TransactionContext`](crate::TransactionContext).
+///
+/// Only the SQL text is recorded. Bind parameters are never captured, so values passed
+/// with `.bind(...)` do not end up in logs or error reports; values inlined into the
+/// SQL string itself are recorded as-is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct JournalEntry {
+    /// The SQL text, with placeholders in place of bind parameters
+    pub sql: String,
+    /// When the statement started, relative to the start of the transaction
+    pub started_after: Duration,
+    /// How long the statement ran, including fetching its rows
+    pub duration: Duration,
+    /// Rows affected, as reported by the server
+    pub rows_affected: u64,
+    /// Rows returned to the caller
+    pub rows_returned: u64,
+    /// Whether the statement failed
+    pub failed: bool,
+}
+
+/// Snapshot of the statements recorded for a transaction.
+///
+/// The journal is bounded: once it holds its capacity, the oldest entries are
+/// discarded and counted in [`discarded`](Self::discarded).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StatementJournal {
+    entries: Vec<JournalEntry>,
+    discarded: u64,
+}
+
+impl StatementJournal {
+    /// Returns the recorded statements, oldest first.
+    pub fn entries(&self) -> &[JournalEntry] {
+        &self.entries
+    }
+
+    /// Returns the number of statements discarded because the journal was full.
+    pub fn discarded(&self) -> u64 {
+        self.discarded
+    }
+
+    /// Returns the most recent statement, which is usually the one that failed.
+    pub fn last(&self) -> Option<&JournalEntry> {
+        self.entries.last()
+    }
+}
+
+impl std::fmt::Display for StatementJournal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.discarded > 0 {
+            writeln!(f, "({} earlier statements discarded)", self.discarded)?;
+        }
+        for entry in &self.entries {
+            writeln!(
+                f,
+                "+{:?} {:?} rows_affected={} rows_returned={}{} {}",
+                entry.started_after,
+                entry.duration,
+                entry.rows_affected,
+                entry.rows_returned,
+                if entry.failed { " FAILED" } else { "" },
+                entry.sql
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Bounded buffer of journal entries, shared with in-flight statements.
+#[derive(Debug, Clone)]
+pub(crate) struct Journal {
+    inner: Arc<Mutex<Buffer>>,
+    started_at: Instant,
+}
+
+#[derive(Debug)]
+struct Buffer {
+    entries: VecDeque<JournalEntry>,
+    capacity: usize,
+    discarded: u64,
+}
+
+impl Journal {
+    pub(crate) fn new(capacity: usize, started_at: Instant) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Buffer {
+                entries: VecDeque::with_capacity(capacity.min(64)),
+                capacity,
+                discarded: 0,
+            })),
+            started_at,
+        }
+    }
+
+    /// Returns a copy of the entries recorded so far.
+    pub(crate) fn snapshot(&self) -> StatementJournal {
+        let buffer = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        StatementJournal {
+            entries: buffer.entries.iter().cloned().collect(),
+            discarded: buffer.discarded,
+        }
+    }
+
+    fn push(&self, entry: JournalEntry) {
+        let mut buffer = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        if buffer.capacity == 0 {
+            buffer.discarded += 1;
+            return;
+        }
+        if buffer.entries.len() == buffer.capacity {
+            buffer.entries.pop_front();
+            buffer.discarded += 1;
+        }
+        buffer.entries.push_back(entry);
+    }
+
+    fn start(&self, sql: &str) -> Recording {
+        let now = Instant::now();
+        Recording {
+            journal: self.clone(),
+            entry: Some(JournalEntry {
+                sql: sql.to_owned(),
+                started_after: now.saturating_duration_since(self.started_at),
+                duration: Duration::ZERO,
+                rows_affected: 0,
+                rows_returned: 0,
+                failed: false,
+            }),
+            started_at: now,
+        }
+    }
+
+    /// Records the statement whose results are streamed by `stream`.
+    pub(crate) fn stream<'e, R: 'e>(
+        &self,
+        sql: &str,
+        stream: BoxStream<'e, Result<Either<MySqlQueryResult, R>, sqlx::Error>>,
+    ) -> BoxStream<'e, Result<Either<MySqlQueryResult, R>, sqlx::Error>> {
+        Box::pin(JournaledStream {
+            inner: stream,
+            recording: self.start(sql),
+        })
+    }
+
+    /// Records the statement whose single optional row is returned by `future`.
+    pub(crate) fn future<'e, R: Send + 'e>(
+        &self,
+        sql: &str,
+        future: BoxFuture<'e, Result<Option<R>, sqlx::Error>>,
+    ) -> BoxFuture<'e, Result<Option<R>, sqlx::Error>> {
+        let mut recording = self.start(sql);
+        Box::pin(async move {
+            let result = future.await;
+            if let Some(entry) = &mut recording.entry {
+                match &result {
+                    Ok(row) => entry.rows_returned = u64::from(row.is_some()),
+                    Err(_) => entry.failed = true,
+                }
+            }
+            result
+        })
+    }
+}
+
+/// An in-flight statement, pushed to the journal when dropped.
+///
+/// Recording on drop also captures statements whose future or stream was abandoned
+/// before completing.
+struct Recording {
+    journal: Journal,
+    entry: Option<JournalEntry>,
+    started_at: Instant,
+}
+
+impl Drop for Recording {
+    fn drop(&mut self) {
+        if let Some(mut entry) = self.entry.take() {
+            entry.duration = self.started_at.elapsed();
+            self.journal.push(entry);
+        }
+    }
+}
+
+struct JournaledStream<'e, R> {
+    inner: BoxStream<'e, Result<Either<MySqlQueryResult, R>, sqlx::Error>>,
+    recording: Recording,
+}
+
+impl<R> Stream for JournaledStream<'_, R> {
+    type Item = Result<Either<MySqlQueryResult, R>, sqlx::Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        let poll = this.inner.as_mut().poll_next(cx);
+        if let (Poll::Ready(Some(item)), Some(entry)) = (&poll, &mut this.recording.entry) {
+            match item {
+                Ok(Either::Left(done)) => entry.rows_affected += done.rows_affected(),
+                Ok(Either::Right(_)) => entry.rows_returned += 1,
+                Err(_) => entry.failed = true,
+            }
+        }
+        poll
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(journal: &Journal, sql: &str, failed: bool) {
+        let mut recording = journal.start(sql);
+        if let Some(entry) = &mut recording.entry {
+            entry.failed = failed;
+        }
+    }
+
+    #[test]
+    fn test_journal_keeps_the_most_recent_statements() {
+        let journal = Journal::new(2, Instant::now());
+        record(&journal, "INSERT INTO a VALUES (?)", false);
+        record(&journal, "INSERT INTO b VALUES (?)", false);
+        record(&journal, "UPDATE c SET x = ?", true);
+
+        let snapshot = journal.snapshot();
+        let sql: Vec<_> = snapshot.entries().iter().map(|e| e.sql.as_str()).collect();
+        assert_eq!(sql, ["INSERT INTO b VALUES (?)", "UPDATE c SET x = ?"]);
+        assert_eq!(snapshot.discarded(), 1);
+        assert!(snapshot.last().is_some_and(|e| e.failed));
+    }
+
+    #[test]
+    fn test_zero_capacity_journal_only_counts() {
+        let journal = Journal::new(0, Instant::now());
+        record(&journal, "SELECT 1", false);
+
+        let snapshot = journal.snapshot();
+        assert!(snapshot.entries().is_empty());
+        assert_eq!(snapshot.discarded(), 1);
+    }
+}
@@ -8,6 +8,7 @@
 //! - **Type-Safe**: Transaction boundaries are enforced at compile time
 //! - **Ergonomic API**: Simple `with_transaction` function for common use cases
 //! - **Nested Transactions**: Support for savepoints to simulate nested transactions
+//! - **Commit Verification**: Optional marker rows resolve commits whose acknowledgement was lost
 //! - **Zero Runtime Overhead**: Thin wrapper around SQLx's native transaction support
 //!
 //! ## Quick Start
@@ -37,7 +38,7 @@
 //!             .bind("Alice")
 //!             .execute(tx.as_executor())
 //!             .await?;
-//!         Ok::<_, sqlx::Error>(())
+//!         Ok::<_, sqlx_transaction_manager::Error>(())
 //!     })
 //! }).await?;
 //! # Ok(())
@@ -70,7 +71,7 @@
 //!             .await?;
 //!
 //!         // Both operations commit together
-//!         Ok::<_, sqlx::Error>(user_id)
+//!         Ok::<_, sqlx_transaction_manager::Error>(user_id)
 //!     })
 //! }).await?;
 //!
@@ -83,7 +84,7 @@
 //!
 //! This library works seamlessly with `sqlx-named-bind`:
 //!
-//! ```rust,no_run
+//! ```rust,ignore
 //! use sqlx::MySqlPool;
 //! use sqlx_transaction_manager::with_transaction;
 //! use sqlx_named_bind::PreparedQuery;
@@ -135,11 +136,11 @@
 //!                     .bind("User created")
 //!                     .execute(nested_tx.as_executor())
 //!                     .await?;
-//!                 Ok::<_, sqlx::Error>(())
+//!                 Ok::<_, sqlx_transaction_manager::Error>(())
 //!             })
 //!         }).await; // If this fails, only the audit log is rolled back
 //!
-//!         Ok::<_, sqlx::Error>(())
+//!         Ok::<_, sqlx_transaction_manager::Error>(())
 //!     })
 //! }).await?;
 //! # Ok(())
@@ -187,10 +188,10 @@
 //!             .await?;
 //!
 //!         // This will cause a rollback
-//!         return Err(sqlx::Error::RowNotFound);
+//!         return Err(sqlx::Error::RowNotFound.into());
 //!
 //!         #[allow(unreachable_code)]
-//!         Ok::<_, sqlx::Error>(())
+//!         Ok::<_, sqlx_transaction_manager::Error>(())
 //!     })
 //! }).await;
 //!
@@ -207,6 +208,28 @@
 //! 3. **Type Safety**: Consumed transactions can't be reused (enforced at compile time)
 //! 4. **Executor Access**: Provides `&mut MySqlConnection` for use with SQLx queries
 //!
+//! ## Cargo Features
+//!
+//! - `anyhow`: closures return `anyhow::Result` in `with_transaction` and `with_nested_transaction`
+//! - `tracing`: emits spans and events for begin, commit, rollback, drop-rollback and savepoints
+//! - `metrics`: records `sqlx_transactions_total` (by `outcome`), `sqlx_transaction_duration_seconds`
+//!   and `sqlx_transaction_retries_total` through the `metrics` facade, labeled with the
+//!   transaction's `label` as `transaction`
+//! - `opentelemetry`: implies `tracing`; adds OpenTelemetry semantic-convention fields (`db.system`,
+//!   `db.name`, `db.operation`, `otel.kind`, `otel.status_code`) and a child span for every
+//!   statement run through the context, for export with `tracing-opentelemetry`
+//! - `macros`: the `#[transactional]` attribute, which runs an async fn's body in a transaction
+//! - `ambient`: the `ambient` module, which makes a transaction reachable from anywhere in a
+//!   tokio task through a task-local
+//! - `axum`: the `axum` module, with a middleware that runs each request in a transaction and
+//!   a `Tx` extractor for handlers
+//! - `tower`: the `tower` module, with a layer that runs each request to an HTTP service, such as
+//!   a tonic server, in a transaction
+//! - `actix`: the `actix` module, with actix-web middleware that runs each request in a
+//!   transaction and a `Tx` extractor for handlers
+//! - `shared`: `SharedTransaction`, a cloneable handle to a transaction for several
+//!   repositories or tasks
+//!
 //! ## Limitations
 //!
 //! - Currently only supports MySQL (PostgreSQL and SQLite support planned)
@@ -217,25 +240,135 @@
 //!
 //! Licensed under either of Apache License, Version 2.0 or MIT license at your option.
 
+pub mod comment;
 pub mod context;
+pub mod deadlock;
+pub mod dropped;
 pub mod error;
 pub mod executor;
+mod instrument;
+pub mod journal;
+pub mod limits;
+pub mod manager;
+pub mod options;
+pub mod pool_ext;
+pub mod registry;
+pub mod retry;
+pub mod verification;
 
 #[cfg(feature = "anyhow")]
 pub mod anyhow_compat;
 
-pub use context::TransactionContext;
-pub use error::{Error, Result};
+#[cfg(feature = "ambient")]
+pub mod ambient;
+
+#[cfg(feature = "axum")]
+pub mod axum;
+
+#[cfg(feature = "tower")]
+pub mod tower;
+
+#[cfg(feature = "actix")]
+pub mod actix;
+
+#[cfg(any(feature = "axum", feature = "tower", feature = "actix"))]
+pub mod request;
+
+#[cfg(feature = "shared")]
+pub mod shared;
+
+pub use comment::SqlComment;
+pub use context::{OwnedTransactionContext, TransactionContext};
+pub use deadlock::{DeadlockReport, DeadlockTransaction};
+pub use dropped::DroppedTransaction;
+pub use error::{Error, ErrorContext, Result};
+pub use journal::{JournalEntry, StatementJournal};
+pub use limits::LongOpenTransaction;
+pub use manager::TransactionManager;
+pub use options::{IsolationLevel, TransactionOptions};
+pub use pool_ext::PoolTransactionExt;
+pub use registry::{LiveTransaction, TransactionRegistry};
+pub use retry::{RetryPolicy, RetryableError};
+pub use verification::{CommitMarker, CommitOutcome};
 
+pub use executor::{
+    with_labeled_transaction, with_nested_transaction_async, with_transaction_async,
+};
 #[cfg(not(feature = "anyhow"))]
 pub use executor::{with_nested_transaction, with_transaction};
 
 #[cfg(feature = "anyhow")]
-pub use anyhow_compat::{with_transaction_anyhow as with_transaction, with_nested_transaction_anyhow as with_nested_transaction};
+pub use anyhow_compat::{
+    with_nested_transaction_anyhow as with_nested_transaction,
+    with_transaction_anyhow as with_transaction,
+};
+
+#[cfg(feature = "shared")]
+pub use shared::{SharedTransaction, SharedTransactionGuard};
+
+#[cfg(feature = "macros")]
+pub use sqlx_transaction_manager_macros::transactional;
+
+/// Support code for the `#[transactional]` macro. Not public API.
+#[cfg(feature = "macros")]
+#[doc(hidden)]
+pub mod __private {
+    use crate::TransactionContext;
+    pub use sqlx::MySqlPool;
+
+    /// Fixes the output type of the body future, so `?` inside it knows which error
+    /// type to convert into.
+    pub fn output<T, F: std::future::Future<Output = T>>(future: F) -> F {
+        future
+    }
+
+    /// Commits the transaction if `result` is `Ok`, and rolls it back otherwise.
+    ///
+    /// Errors of type [`crate::Error`] are handled like in
+    /// [`with_transaction_async`](crate::with_transaction_async): they carry the
+    /// transaction's details and are diagnosed and recorded. Other error types only roll
+    /// the transaction back, as the crate cannot attach anything to them.
+    pub async fn finish<T, E>(tx_ctx: TransactionContext<'_>, result: Result<T, E>) -> Result<T, E>
+    where
+        E: From<crate::Error> + 'static,
+    {
+        match result {
+            Ok(value) => {
+                tx_ctx.commit().await?;
+                Ok(value)
+            }
+            Err(e) => match downcast_error(e) {
+                Ok(e) => Err(tx_ctx.abandon(e).await.into()),
+                Err(e) => {
+                    let _ = tx_ctx.rollback().await;
+                    Err(e)
+                }
+            },
+        }
+    }
+
+    /// Returns `error` as a [`crate::Error`] if that is its type.
+    fn downcast_error<E: 'static>(error: E) -> Result<crate::Error, E> {
+        let mut error = Some(error);
+        match (&mut error as &mut dyn std::any::Any).downcast_mut::<Option<crate::Error>>() {
+            Some(error) => Ok(error.take().expect("error is set")),
+            None => Err(error.expect("error is set")),
+        }
+    }
+}
 
 /// Convenience re-exports for common use cases
 pub mod prelude {
     pub use crate::context::TransactionContext;
     pub use crate::error::{Error, Result};
-    pub use crate::executor::{with_nested_transaction, with_transaction};
+    pub use crate::executor::{
+        with_labeled_transaction, with_nested_transaction, with_nested_transaction_async,
+        with_transaction, with_transaction_async,
+    };
+    pub use crate::manager::TransactionManager;
+    pub use crate::options::{IsolationLevel, TransactionOptions};
+    pub use crate::pool_ext::PoolTransactionExt;
+    pub use crate::retry::RetryPolicy;
+    #[cfg(feature = "macros")]
+    pub use crate::transactional;
 }
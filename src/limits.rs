@@ -0,0 +1,134 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Details passed to a long-open transaction callback.
+///
+/// See [`TransactionOptions::on_long_open`](crate::TransactionOptions::on_long_open).
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub struct LongOpenTransaction<'a> {
+    /// The transaction's label, if any
+    pub label: Option<&'a str>,
+    /// How long the transaction has been open
+    pub elapsed: Duration,
+    /// The configured warning threshold
+    pub threshold: Duration,
+}
+
+/// Callback invoked when a transaction stays open past its warning threshold.
+#[derive(Clone)]
+pub(crate) struct LongOpenHook(Arc<dyn Fn(&LongOpenTransaction<'_>) + Send + Sync>);
+
+impl LongOpenHook {
+    pub(crate) fn new(f: impl Fn(&LongOpenTransaction<'_>) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(f))
+    }
+}
+
+impl fmt::Debug for LongOpenHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("LongOpenHook")
+    }
+}
+
+/// How long a transaction may stay open, configured through `TransactionOptions`.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct DurationLimits {
+    pub(crate) warn_after: Option<Duration>,
+    pub(crate) on_long_open: Option<LongOpenHook>,
+    pub(crate) max_duration: Option<Duration>,
+}
+
+/// Per-transaction limit tracking, owned by a `TransactionContext`.
+///
+/// There is no background timer: limits are checked whenever the transaction is
+/// used, and when it is committed, rolled back or dropped.
+#[derive(Debug, Default)]
+pub(crate) struct LimitState {
+    limits: DurationLimits,
+    warned: bool,
+}
+
+impl LimitState {
+    pub(crate) fn new(limits: DurationLimits) -> Self {
+        Self {
+            limits,
+            warned: false,
+        }
+    }
+
+    /// Reports the transaction once if it has been open longer than the warning threshold.
+    ///
+    /// Returns the threshold that was crossed, so the caller can log it.
+    pub(crate) fn check(&mut self, label: Option<&str>, elapsed: Duration) -> Option<Duration> {
+        let threshold = self.limits.warn_after?;
+        if self.warned || elapsed < threshold {
+            return None;
+        }
+
+        self.warned = true;
+        if let Some(LongOpenHook(hook)) = &self.limits.on_long_open {
+            hook(&LongOpenTransaction {
+                label,
+                elapsed,
+                threshold,
+            });
+        }
+        Some(threshold)
+    }
+
+    /// Returns the hard limit if `elapsed` exceeds it.
+    pub(crate) fn exceeded(&self, elapsed: Duration) -> Option<Duration> {
+        self.limits.max_duration.filter(|&limit| elapsed > limit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_long_open_callback_fires_once() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let hook_seen = Arc::clone(&seen);
+        let mut state = LimitState::new(DurationLimits {
+            warn_after: Some(Duration::from_secs(1)),
+            on_long_open: Some(LongOpenHook::new(move |tx| {
+                hook_seen
+                    .lock()
+                    .unwrap()
+                    .push((tx.label.map(str::to_owned), tx.elapsed));
+            })),
+            max_duration: None,
+        });
+
+        assert_eq!(
+            state.check(Some("checkout"), Duration::from_millis(500)),
+            None
+        );
+        assert_eq!(
+            state.check(Some("checkout"), Duration::from_secs(2)),
+            Some(Duration::from_secs(1))
+        );
+        assert_eq!(state.check(Some("checkout"), Duration::from_secs(3)), None);
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![(Some("checkout".to_owned()), Duration::from_secs(2))]
+        );
+    }
+
+    #[test]
+    fn test_hard_limit() {
+        let state = LimitState::new(DurationLimits {
+            max_duration: Some(Duration::from_secs(5)),
+            ..DurationLimits::default()
+        });
+        assert_eq!(state.exceeded(Duration::from_secs(5)), None);
+        assert_eq!(
+            state.exceeded(Duration::from_secs(6)),
+            Some(Duration::from_secs(5))
+        );
+    }
+}
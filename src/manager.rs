@@ -0,0 +1,167 @@
+use crate::context::TransactionContext;
+use crate::executor::with_transaction_retry;
+use crate::options::TransactionOptions;
+use crate::retry::RetryPolicy;
+use sqlx::MySqlPool;
+use std::future::Future;
+use std::pin::Pin;
+
+/// A cloneable service object holding a pool together with default transaction policies.
+///
+/// Inject one `TransactionManager` into handlers instead of passing the pool and loose
+/// settings to every call. Every transaction it starts uses the default
+/// [`TransactionOptions`] and [`RetryPolicy`], unless a call overrides them with
+/// [`run_with`](Self::run_with) or [`begin_with`](Self::begin_with).
+///
+/// Cloning is cheap: the pool is reference-counted.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use sqlx::MySqlPool;
+/// use sqlx_transaction_manager::{IsolationLevel, RetryPolicy, TransactionManager, TransactionOptions};
+///
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// # let pool = MySqlPool::connect("mysql://localhost/test").await?;
+/// let manager = TransactionManager::new(pool)
+///     .with_options(TransactionOptions::new().isolation_level(IsolationLevel::ReadCommitted))
+///     .with_retry(RetryPolicy::attempts(3));
+///
+/// manager.run(|tx| {
+///     Box::pin(async move {
+///         sqlx::query("INSERT INTO users (name) VALUES (?)")
+///             .bind("Alice")
+///             .execute(tx.as_executor())
+///             .await?;
+///         Ok::<_, sqlx_transaction_manager::Error>(())
+///     })
+/// }).await?;
+///
+/// // Override the defaults for a single call
+/// let options = manager.options().clone().isolation_level(IsolationLevel::Serializable);
+/// let mut tx = manager.begin_with(options).await?;
+/// tx.commit().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct TransactionManager {
+    pool: MySqlPool,
+    options: TransactionOptions,
+    retry: RetryPolicy,
+}
+
+impl TransactionManager {
+    /// Creates a manager with default options and no retries.
+    pub fn new(pool: MySqlPool) -> Self {
+        Self {
+            pool,
+            options: TransactionOptions::default(),
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    /// Sets the default options for transactions started by this manager.
+    pub fn with_options(mut self, options: TransactionOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Sets the default retry policy used by [`run`](Self::run) and its variants.
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Returns the underlying pool.
+    pub fn pool(&self) -> &MySqlPool {
+        &self.pool
+    }
+
+    /// Returns the default transaction options.
+    pub fn options(&self) -> &TransactionOptions {
+        &self.options
+    }
+
+    /// Returns the default retry policy.
+    pub fn retry_policy(&self) -> RetryPolicy {
+        self.retry
+    }
+
+    /// Begins a [`TransactionContext`] using the default options.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the transaction cannot be started.
+    pub async fn begin(&self) -> crate::Result<TransactionContext<'static>> {
+        self.begin_with(self.options.clone()).await
+    }
+
+    /// Begins a [`TransactionContext`] using `options` instead of the defaults.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the transaction cannot be started.
+    pub async fn begin_with(
+        &self,
+        options: TransactionOptions,
+    ) -> crate::Result<TransactionContext<'static>> {
+        TransactionContext::begin_with(&self.pool, options).await
+    }
+
+    /// Executes a function within a transaction using the default options and retry policy.
+    ///
+    /// The function may be called more than once if the retry policy allows it.
+    /// See [`with_transaction_retry`].
+    pub async fn run<F, T>(&self, f: F) -> crate::Result<T>
+    where
+        F: for<'a> FnMut(
+            &'a mut TransactionContext<'_>,
+        ) -> Pin<Box<dyn Future<Output = crate::Result<T>> + Send + 'a>>,
+        T: Send,
+    {
+        self.run_with(self.options.clone(), f).await
+    }
+
+    /// Executes a function within a read-only transaction, otherwise using the defaults.
+    pub async fn run_read_only<F, T>(&self, f: F) -> crate::Result<T>
+    where
+        F: for<'a> FnMut(
+            &'a mut TransactionContext<'_>,
+        ) -> Pin<Box<dyn Future<Output = crate::Result<T>> + Send + 'a>>,
+        T: Send,
+    {
+        self.run_with(self.options.clone().read_only(), f).await
+    }
+
+    /// Executes a function within a transaction using `options` instead of the defaults.
+    ///
+    /// The default retry policy still applies.
+    pub async fn run_with<F, T>(&self, options: TransactionOptions, f: F) -> crate::Result<T>
+    where
+        F: for<'a> FnMut(
+            &'a mut TransactionContext<'_>,
+        ) -> Pin<Box<dyn Future<Output = crate::Result<T>> + Send + 'a>>,
+        T: Send,
+    {
+        with_transaction_retry(&self.pool, options, self.retry, f).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_send<T: Send>(_: &T) {}
+
+    #[tokio::test]
+    async fn test_manager_is_cloneable_and_futures_are_send() {
+        let pool = MySqlPool::connect_lazy("mysql://localhost/test").unwrap();
+        let manager = TransactionManager::new(pool).with_retry(RetryPolicy::attempts(2));
+        let cloned = manager.clone();
+        assert_eq!(cloned.retry_policy().max_attempts(), 2);
+
+        let run = cloned.run(|_tx| Box::pin(async move { Ok(()) }));
+        assert_send(&run);
+    }
+}
@@ -0,0 +1,327 @@
+use crate::comment::SqlComment;
+use crate::dropped::{DropPolicy, DroppedTransaction};
+use crate::limits::{DurationLimits, LongOpenHook, LongOpenTransaction};
+use crate::registry::TransactionRegistry;
+use crate::verification::CommitMarker;
+use std::borrow::Cow;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Transaction isolation level, applied with `SET TRANSACTION ISOLATION LEVEL`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IsolationLevel {
+    /// `READ UNCOMMITTED`
+    ReadUncommitted,
+    /// `READ COMMITTED`
+    ReadCommitted,
+    /// `REPEATABLE READ` (the InnoDB default)
+    RepeatableRead,
+    /// `SERIALIZABLE`
+    Serializable,
+}
+
+impl IsolationLevel {
+    /// Returns the SQL spelling of the isolation level.
+    pub fn as_sql(self) -> &'static str {
+        match self {
+            IsolationLevel::ReadUncommitted => "READ UNCOMMITTED",
+            IsolationLevel::ReadCommitted => "READ COMMITTED",
+            IsolationLevel::RepeatableRead => "REPEATABLE READ",
+            IsolationLevel::Serializable => "SERIALIZABLE",
+        }
+    }
+}
+
+impl fmt::Display for IsolationLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_sql())
+    }
+}
+
+/// Settings applied when a [`TransactionContext`](crate::TransactionContext) begins.
+///
+/// The default options begin a plain transaction, equivalent to
+/// [`TransactionContext::begin`](crate::TransactionContext::begin).
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use sqlx::MySqlPool;
+/// use sqlx_transaction_manager::{CommitMarker, TransactionContext, TransactionOptions};
+///
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// # let pool = MySqlPool::connect("mysql://localhost/test").await?;
+/// let options = TransactionOptions::new()
+///     .verify_commit(CommitMarker::new("tx_commit_markers"));
+/// let tx = TransactionContext::begin_with(&pool, options).await?;
+/// tx.commit().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TransactionOptions {
+    pub(crate) commit_marker: Option<CommitMarker>,
+    pub(crate) read_only: bool,
+    pub(crate) isolation_level: Option<IsolationLevel>,
+    pub(crate) label: Option<Cow<'static, str>>,
+    pub(crate) limits: DurationLimits,
+    pub(crate) journal_capacity: Option<usize>,
+    pub(crate) registry: Option<TransactionRegistry>,
+    pub(crate) capture_connection_id: bool,
+    pub(crate) diagnose_deadlocks: bool,
+    pub(crate) sql_comment: Option<SqlComment>,
+    pub(crate) on_drop: DropPolicy,
+}
+
+impl TransactionOptions {
+    /// Creates the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enables commit-outcome verification using the given marker table.
+    ///
+    /// See [`CommitMarker`] for the required table layout.
+    pub fn verify_commit(mut self, marker: CommitMarker) -> Self {
+        self.commit_marker = Some(marker);
+        self
+    }
+
+    /// Starts the transaction with `START TRANSACTION READ ONLY`.
+    ///
+    /// Writes inside a read-only transaction fail, and no commit marker is written
+    /// since there is nothing to verify.
+    pub fn read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+
+    /// Sets the isolation level of the transaction.
+    ///
+    /// When unset, the session's default isolation level is used.
+    pub fn isolation_level(mut self, level: IsolationLevel) -> Self {
+        self.isolation_level = Some(level);
+        self
+    }
+
+    /// Attaches a label naming the business operation, such as `"checkout.place_order"`.
+    ///
+    /// The label is readable through [`TransactionContext::label`](crate::TransactionContext::label)
+    /// and is attached to returned errors and emitted telemetry.
+    pub fn label(mut self, label: impl Into<Cow<'static, str>>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// Reports the transaction once it has been open longer than `threshold`.
+    ///
+    /// The report is a `tracing` warning when the `tracing` feature is enabled, and a
+    /// call to the [`on_long_open`](Self::on_long_open) callback when one is set. It is
+    /// emitted at most once per transaction.
+    ///
+    /// There is no background timer: the duration is checked whenever the transaction's
+    /// executor is used, and when it is committed, rolled back or dropped. A transaction
+    /// that sits idle, for example one leaked into a long-lived task, is therefore not
+    /// reported until it is touched again. To find those, poll
+    /// [`TransactionRegistry::older_than`](crate::TransactionRegistry::older_than)
+    /// with a [`registry`](Self::registry) configured.
+    pub fn warn_after(mut self, threshold: Duration) -> Self {
+        self.limits.warn_after = Some(threshold);
+        self
+    }
+
+    /// Sets the callback invoked when the [`warn_after`](Self::warn_after) threshold
+    /// is crossed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    /// use sqlx_transaction_manager::TransactionOptions;
+    ///
+    /// let options = TransactionOptions::new()
+    ///     .label("reports.nightly")
+    ///     .warn_after(Duration::from_secs(5))
+    ///     .on_long_open(|tx| {
+    ///         eprintln!("{:?} open for {:?}", tx.label, tx.elapsed);
+    ///     });
+    /// ```
+    pub fn on_long_open(
+        mut self,
+        callback: impl Fn(&LongOpenTransaction<'_>) + Send + Sync + 'static,
+    ) -> Self {
+        self.limits.on_long_open = Some(LongOpenHook::new(callback));
+        self
+    }
+
+    /// Sets a hard limit on how long the transaction may stay open.
+    ///
+    /// Committing a transaction that has been open longer than `limit` rolls it back
+    /// instead and fails with [`Error::TimeLimitExceeded`](crate::Error::TimeLimitExceeded).
+    ///
+    /// The limit is enforced at commit only. Nothing interrupts a transaction that is
+    /// still running or idle past it, so its locks are held until it finishes; see
+    /// [`warn_after`](Self::warn_after).
+    pub fn max_duration(mut self, limit: Duration) -> Self {
+        self.limits.max_duration = Some(limit);
+        self
+    }
+
+    /// Records the statements executed through the transaction's executor
+    /// implementation, keeping the most recent `capacity` of them.
+    ///
+    /// Each entry holds the SQL text, timing and row counts; bind parameters are never
+    /// recorded. The journal is readable through
+    /// [`TransactionContext::journal`](crate::TransactionContext::journal) and is attached
+    /// to errors returned for the transaction, see [`Error::journal`](crate::Error::journal).
+    ///
+    /// Only statements run with `&mut tx` as the executor are recorded; statements run
+    /// on the raw connection from [`as_executor`](crate::TransactionContext::as_executor)
+    /// bypass the journal.
+    pub fn journal(mut self, capacity: usize) -> Self {
+        self.journal_capacity = Some(capacity);
+        self
+    }
+
+    /// Registers the transaction in `registry` for as long as its context is alive.
+    ///
+    /// See [`TransactionRegistry`] for listing live transactions and finding leaked ones.
+    pub fn registry(mut self, registry: TransactionRegistry) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
+    /// Fetches the connection's `CONNECTION_ID()` when the transaction begins.
+    ///
+    /// The ID is otherwise only fetched on demand through
+    /// [`TransactionContext::connection_id`](crate::TransactionContext::connection_id).
+    /// Fetching it up front costs one round trip, but means it is included in every
+    /// error returned for the transaction and in its `tracing` span.
+    pub fn capture_connection_id(mut self) -> Self {
+        self.capture_connection_id = true;
+        self
+    }
+
+    /// Attaches a [`DeadlockReport`](crate::DeadlockReport) to deadlock errors.
+    ///
+    /// When the transaction fails with a deadlock (MySQL error 1213), the
+    /// `LATEST DETECTED DEADLOCK` section of `SHOW ENGINE INNODB STATUS` is fetched on a
+    /// separate pool connection, parsed, and attached to the returned error, see
+    /// [`Error::deadlock_report`](crate::Error::deadlock_report). This requires the
+    /// `PROCESS` privilege; without it, errors are returned without a report.
+    ///
+    /// Errors returned by the `with_transaction` family and by
+    /// [`commit`](crate::TransactionContext::commit) are diagnosed automatically. When
+    /// managing the context by hand, pass errors through
+    /// [`TransactionContext::diagnose`](crate::TransactionContext::diagnose).
+    pub fn diagnose_deadlocks(mut self) -> Self {
+        self.diagnose_deadlocks = true;
+        self
+    }
+
+    /// Prepends a sqlcommenter-style comment to every statement the transaction runs.
+    ///
+    /// The comment is applied by the executor implementation of `&mut TransactionContext`,
+    /// so queries run with `&mut tx` as their executor are tagged without changes.
+    /// Statements run on the raw connection from
+    /// [`as_executor`](crate::TransactionContext::as_executor), and queries built from an
+    /// already prepared statement, are not tagged. Tagged statements are not kept in the
+    /// connection's prepared statement cache. See [`SqlComment`] for the format and the
+    /// cost.
+    pub fn sql_comment(mut self, comment: SqlComment) -> Self {
+        self.sql_comment = Some(comment);
+        self
+    }
+
+    /// Sets a callback invoked when the context is dropped without `commit()` or
+    /// `rollback()`.
+    ///
+    /// The transaction is rolled back either way. The callback receives the label, how
+    /// long the transaction was open, and whether the drop happened while unwinding from
+    /// a panic. Explicit rollbacks, including those done by the `with_transaction` family
+    /// on error, do not invoke it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sqlx_transaction_manager::TransactionOptions;
+    ///
+    /// let options = TransactionOptions::new().on_drop(|tx| {
+    ///     eprintln!(
+    ///         "{:?} dropped after {:?} (panicking: {})",
+    ///         tx.label, tx.elapsed, tx.panicking
+    ///     );
+    /// });
+    /// ```
+    pub fn on_drop(
+        mut self,
+        callback: impl Fn(&DroppedTransaction<'_>) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_drop.hook = Some(Arc::new(callback));
+        self
+    }
+
+    /// Panics when the context is dropped without `commit()` or `rollback()`, to catch
+    /// missing commits in tests.
+    ///
+    /// Only takes effect in debug builds, and never panics while the thread is already
+    /// unwinding.
+    pub fn panic_on_drop(mut self) -> Self {
+        self.on_drop.panic = true;
+        self
+    }
+
+    /// Returns the statement used to start the transaction, if it differs from `BEGIN`.
+    pub(crate) fn begin_statement(&self) -> Option<String> {
+        let start = if self.read_only {
+            "START TRANSACTION READ ONLY"
+        } else {
+            "START TRANSACTION"
+        };
+
+        match (self.isolation_level, self.read_only) {
+            (None, false) => None,
+            (None, true) => Some(start.to_owned()),
+            // The isolation level only applies to the next transaction on the session,
+            // so it is sent together with the statement that starts it.
+            (Some(level), _) => Some(format!("SET TRANSACTION ISOLATION LEVEL {level}; {start}")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_options_use_plain_begin() {
+        assert_eq!(TransactionOptions::new().begin_statement(), None);
+    }
+
+    #[test]
+    fn test_begin_statement_combines_isolation_and_access_mode() {
+        assert_eq!(
+            TransactionOptions::new()
+                .read_only()
+                .begin_statement()
+                .as_deref(),
+            Some("START TRANSACTION READ ONLY")
+        );
+        assert_eq!(
+            TransactionOptions::new()
+                .isolation_level(IsolationLevel::ReadCommitted)
+                .begin_statement()
+                .as_deref(),
+            Some("SET TRANSACTION ISOLATION LEVEL READ COMMITTED; START TRANSACTION")
+        );
+        assert_eq!(
+            TransactionOptions::new()
+                .read_only()
+                .isolation_level(IsolationLevel::Serializable)
+                .begin_statement()
+                .as_deref(),
+            Some("SET TRANSACTION ISOLATION LEVEL SERIALIZABLE; START TRANSACTION READ ONLY")
+        );
+    }
+}
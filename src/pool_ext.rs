@@ -0,0 +1,148 @@
+use crate::context::TransactionContext;
+use crate::executor::{with_transaction, with_transaction_options};
+use crate::options::TransactionOptions;
+use sqlx::MySqlPool;
+use std::future::Future;
+use std::pin::Pin;
+
+/// Extension methods exposing the crate's transaction entry points on a pool.
+///
+/// Import it through the [`prelude`](crate::prelude) to write `pool.transaction(...)`
+/// instead of `with_transaction(&pool, ...)`.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use sqlx::MySqlPool;
+/// use sqlx_transaction_manager::prelude::*;
+///
+/// # async fn example() -> Result<()> {
+/// # let pool = MySqlPool::connect("mysql://localhost/test").await?;
+/// pool.transaction(|tx| {
+///     Box::pin(async move {
+///         sqlx::query("INSERT INTO users (name) VALUES (?)")
+///             .bind("Alice")
+///             .execute(tx.as_executor())
+///             .await?;
+///         Ok(())
+///     })
+/// }).await?;
+///
+/// let mut tx = pool.begin_context().await?;
+/// sqlx::query("DELETE FROM sessions").execute(&mut tx).await?;
+/// tx.commit().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub trait PoolTransactionExt {
+    /// Begins a new [`TransactionContext`]. See [`TransactionContext::begin`].
+    fn begin_context(
+        &self,
+    ) -> impl Future<Output = crate::Result<TransactionContext<'static>>> + Send;
+
+    /// Begins a new [`TransactionContext`] with the given options.
+    /// See [`TransactionContext::begin_with`].
+    fn begin_context_with(
+        &self,
+        options: TransactionOptions,
+    ) -> impl Future<Output = crate::Result<TransactionContext<'static>>> + Send;
+
+    /// Executes a function within a transaction. See [`with_transaction`].
+    fn transaction<F, T>(&self, f: F) -> impl Future<Output = crate::Result<T>> + Send
+    where
+        F: for<'a> FnOnce(
+                &'a mut TransactionContext<'_>,
+            )
+                -> Pin<Box<dyn Future<Output = crate::Result<T>> + Send + 'a>>
+            + Send,
+        T: Send;
+
+    /// Executes a function within a transaction started with the given options.
+    /// See [`with_transaction_options`].
+    fn transaction_with<F, T>(
+        &self,
+        options: TransactionOptions,
+        f: F,
+    ) -> impl Future<Output = crate::Result<T>> + Send
+    where
+        F: for<'a> FnOnce(
+                &'a mut TransactionContext<'_>,
+            )
+                -> Pin<Box<dyn Future<Output = crate::Result<T>> + Send + 'a>>
+            + Send,
+        T: Send;
+
+    /// Executes a function within a read-only transaction.
+    /// See [`TransactionOptions::read_only`].
+    fn read_only_transaction<F, T>(&self, f: F) -> impl Future<Output = crate::Result<T>> + Send
+    where
+        F: for<'a> FnOnce(
+                &'a mut TransactionContext<'_>,
+            )
+                -> Pin<Box<dyn Future<Output = crate::Result<T>> + Send + 'a>>
+            + Send,
+        T: Send,
+    {
+        self.transaction_with(TransactionOptions::new().read_only(), f)
+    }
+}
+
+impl PoolTransactionExt for MySqlPool {
+    fn begin_context(
+        &self,
+    ) -> impl Future<Output = crate::Result<TransactionContext<'static>>> + Send {
+        TransactionContext::begin(self)
+    }
+
+    fn begin_context_with(
+        &self,
+        options: TransactionOptions,
+    ) -> impl Future<Output = crate::Result<TransactionContext<'static>>> + Send {
+        TransactionContext::begin_with(self, options)
+    }
+
+    fn transaction<F, T>(&self, f: F) -> impl Future<Output = crate::Result<T>> + Send
+    where
+        F: for<'a> FnOnce(
+                &'a mut TransactionContext<'_>,
+            )
+                -> Pin<Box<dyn Future<Output = crate::Result<T>> + Send + 'a>>
+            + Send,
+        T: Send,
+    {
+        with_transaction(self, f)
+    }
+
+    fn transaction_with<F, T>(
+        &self,
+        options: TransactionOptions,
+        f: F,
+    ) -> impl Future<Output = crate::Result<T>> + Send
+    where
+        F: for<'a> FnOnce(
+                &'a mut TransactionContext<'_>,
+            )
+                -> Pin<Box<dyn Future<Output = crate::Result<T>> + Send + 'a>>
+            + Send,
+        T: Send,
+    {
+        with_transaction_options(self, options, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_send<T: Send>(_: &T) {}
+
+    #[tokio::test]
+    async fn test_transaction_futures_are_send() {
+        let pool = MySqlPool::connect_lazy("mysql://localhost/test").unwrap();
+
+        let write = pool.transaction(|_tx| Box::pin(async move { Ok(()) }));
+        let read = pool.read_only_transaction(|_tx| Box::pin(async move { Ok(1) }));
+        assert_send(&write);
+        assert_send(&read);
+    }
+}
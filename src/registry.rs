@@ -0,0 +1,255 @@
+//! Registry of in-flight transactions for leak detection.
+//!
+//! Enable it with [`TransactionOptions::registry`](crate::TransactionOptions::registry).
+
+use std::backtrace::Backtrace;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Tracks every live [`TransactionContext`](crate::TransactionContext) begun with it.
+///
+/// A transaction is registered when it begins and removed when its context is
+/// committed, rolled back or dropped. A context that stays registered for a long time
+/// has usually leaked into a long-lived task and is pinning a pool connection.
+///
+/// Registries are cheap to clone; clones share the same set of transactions. Use
+/// [`global`](Self::global) for a process-wide registry, or create one per
+/// [`TransactionManager`](crate::TransactionManager).
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::time::Duration;
+/// use sqlx::MySqlPool;
+/// use sqlx_transaction_manager::{TransactionContext, TransactionOptions, TransactionRegistry};
+///
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// # let pool = MySqlPool::connect("mysql://localhost/test").await?;
+/// let registry = TransactionRegistry::global();
+/// let options = TransactionOptions::new().registry(registry.clone());
+/// let tx = TransactionContext::begin_with(&pool, options).await?;
+///
+/// for leaked in registry.older_than(Duration::from_secs(60)) {
+///     eprintln!("{leaked}");
+/// }
+/// tx.commit().await?;
+/// assert!(registry.is_empty());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct TransactionRegistry {
+    inner: Arc<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    live: Mutex<HashMap<u64, LiveTransaction>>,
+    next_id: AtomicU64,
+    force_backtraces: bool,
+}
+
+impl TransactionRegistry {
+    /// Creates an empty registry.
+    ///
+    /// Creation backtraces are captured according to `RUST_BACKTRACE` /
+    /// `RUST_LIB_BACKTRACE`, like [`Backtrace::capture`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty registry that always captures creation backtraces.
+    ///
+    /// Capturing a backtrace is relatively expensive; prefer this in tests and
+    /// debugging builds.
+    pub fn with_backtraces() -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                force_backtraces: true,
+                ..Inner::default()
+            }),
+        }
+    }
+
+    /// Returns the process-wide registry.
+    pub fn global() -> &'static TransactionRegistry {
+        static GLOBAL: OnceLock<TransactionRegistry> = OnceLock::new();
+        GLOBAL.get_or_init(TransactionRegistry::new)
+    }
+
+    /// Returns every live transaction, oldest first.
+    pub fn live(&self) -> Vec<LiveTransaction> {
+        let mut live: Vec<_> = self.lock().values().cloned().collect();
+        live.sort_by_key(|tx| (tx.started_at, tx.id));
+        live
+    }
+
+    /// Returns the live transactions that have been open longer than `threshold`,
+    /// oldest first.
+    pub fn older_than(&self, threshold: Duration) -> Vec<LiveTransaction> {
+        let mut live = self.live();
+        live.retain(|tx| tx.age() > threshold);
+        live
+    }
+
+    /// Returns the number of live transactions.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Returns `true` if no transaction is live.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Registers a transaction that has just begun.
+    pub(crate) fn register(&self, label: Option<Cow<'static, str>>) -> Registration {
+        let id = self.inner.next_id.fetch_add(1, Ordering::Relaxed);
+        let backtrace = if self.inner.force_backtraces {
+            Backtrace::force_capture()
+        } else {
+            Backtrace::capture()
+        };
+        self.lock().insert(
+            id,
+            LiveTransaction {
+                id,
+                label,
+                started_at: Instant::now(),
+                backtrace: Arc::new(backtrace),
+            },
+        );
+
+        Registration {
+            registry: self.clone(),
+            id,
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<u64, LiveTransaction>> {
+        self.inner.live.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl fmt::Debug for TransactionRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TransactionRegistry")
+            .field("live", &self.len())
+            .finish()
+    }
+}
+
+/// A transaction that is currently registered in a [`TransactionRegistry`].
+#[derive(Debug, Clone)]
+pub struct LiveTransaction {
+    id: u64,
+    label: Option<Cow<'static, str>>,
+    started_at: Instant,
+    backtrace: Arc<Backtrace>,
+}
+
+impl LiveTransaction {
+    /// Returns the identifier of the transaction, unique within its registry.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Returns the transaction's label, if any.
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    /// Returns when the transaction began.
+    pub fn started_at(&self) -> Instant {
+        self.started_at
+    }
+
+    /// Returns how long the transaction has been open.
+    pub fn age(&self) -> Duration {
+        self.started_at.elapsed()
+    }
+
+    /// Returns the backtrace captured where the transaction began.
+    ///
+    /// The backtrace is empty unless capturing was enabled, see
+    /// [`TransactionRegistry::new`].
+    pub fn backtrace(&self) -> &Backtrace {
+        &self.backtrace
+    }
+}
+
+impl fmt::Display for LiveTransaction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.label {
+            Some(label) => write!(f, "transaction #{} `{label}`", self.id)?,
+            None => write!(f, "transaction #{}", self.id)?,
+        }
+        write!(f, " open for {:?}", self.age())?;
+        if let std::backtrace::BacktraceStatus::Captured = self.backtrace.status() {
+            write!(f, ", begun at:\n{}", self.backtrace)?;
+        }
+        Ok(())
+    }
+}
+
+/// Keeps a transaction registered until dropped.
+#[derive(Debug)]
+pub(crate) struct Registration {
+    registry: TransactionRegistry,
+    id: u64,
+}
+
+impl Registration {
+    pub(crate) fn id(&self) -> u64 {
+        self.id
+    }
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        self.registry.lock().remove(&self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transactions_are_listed_until_dropped() {
+        let registry = TransactionRegistry::new();
+        let first = registry.register(Some("checkout.place_order".into()));
+        let second = registry.register(None);
+
+        let live = registry.live();
+        assert_eq!(live.len(), 2);
+        assert_eq!(live[0].label(), Some("checkout.place_order"));
+        assert_eq!(live[1].label(), None);
+
+        drop(first);
+        assert_eq!(registry.len(), 1);
+        assert_eq!(registry.live()[0].id(), 1);
+
+        drop(second);
+        assert!(registry.is_empty());
+    }
+
+    #[test]
+    fn test_older_than_filters_by_age() {
+        let registry = TransactionRegistry::with_backtraces();
+        let _tx = registry.register(None);
+        std::thread::sleep(Duration::from_millis(1));
+
+        assert_eq!(registry.older_than(Duration::from_secs(3600)).len(), 0);
+        let old = registry.older_than(Duration::ZERO);
+        assert_eq!(old.len(), 1);
+        assert_eq!(
+            old[0].backtrace().status(),
+            std::backtrace::BacktraceStatus::Captured
+        );
+    }
+}
@@ -0,0 +1,177 @@
+//! Transactions scoped to a request, shared by the web framework integrations.
+//!
+//! The `axum`, `tower` and `actix` integrations begin a
+//! [`RequestTransaction`] for each request, put it in the request's extensions, and
+//! finish it once the response is ready.
+
+use crate::context::TransactionContext;
+use futures_core::future::BoxFuture;
+use std::fmt;
+use std::future::Future;
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+use tokio::sync::{Mutex, OwnedMutexGuard};
+
+/// Handle to the transaction of the request being handled.
+///
+/// Handles are cheap to clone and share the same transaction. Call
+/// [`lock`](Self::lock) to run queries on it.
+#[derive(Clone)]
+pub struct RequestTransaction {
+    inner: Arc<Mutex<State>>,
+}
+
+struct State {
+    tx: Option<TransactionContext<'static>>,
+    commit: Option<bool>,
+    after_commit: Vec<BoxFuture<'static, ()>>,
+}
+
+impl RequestTransaction {
+    pub(crate) fn new(tx: TransactionContext<'static>) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(State {
+                tx: Some(tx),
+                commit: None,
+                after_commit: Vec::new(),
+            })),
+        }
+    }
+
+    /// Locks the transaction for running queries.
+    ///
+    /// The request's transaction is finished only once every guard has been dropped, so
+    /// do not keep the guard beyond the handler.
+    ///
+    /// Returns [`Error::AlreadyConsumed`](crate::Error::AlreadyConsumed) if the
+    /// transaction has already been finished.
+    pub async fn lock(&self) -> crate::Result<RequestTransactionGuard> {
+        RequestTransactionGuard::new(Arc::clone(&self.inner).lock_owned().await)
+    }
+
+    /// Locks the transaction if no one else holds it.
+    ///
+    /// Returns `None` if the transaction is locked elsewhere.
+    pub fn try_lock(&self) -> Option<crate::Result<RequestTransactionGuard>> {
+        let state = Arc::clone(&self.inner).try_lock_owned().ok()?;
+        Some(RequestTransactionGuard::new(state))
+    }
+
+    /// Commits or rolls back the transaction, then runs the after-commit work.
+    ///
+    /// The decision made with [`RequestTransactionGuard::set_commit`] wins over
+    /// `commit_by_default`. Does nothing if the transaction has already been finished.
+    pub(crate) async fn finish(&self, commit_by_default: bool) -> crate::Result<()> {
+        let (tx, commit, after_commit) = {
+            let mut state = self.inner.lock().await;
+            (
+                state.tx.take(),
+                state.commit,
+                std::mem::take(&mut state.after_commit),
+            )
+        };
+        let Some(tx) = tx else {
+            return Ok(());
+        };
+
+        if commit.unwrap_or(commit_by_default) {
+            tx.commit().await?;
+            for work in after_commit {
+                work.await;
+            }
+        } else {
+            let _ = tx.rollback().await;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for RequestTransaction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RequestTransaction").finish_non_exhaustive()
+    }
+}
+
+/// Exclusive access to a request's transaction.
+///
+/// Dereferences to the [`TransactionContext`], so `&mut *guard` can be passed to sqlx
+/// queries.
+pub struct RequestTransactionGuard {
+    state: OwnedMutexGuard<State>,
+}
+
+impl RequestTransactionGuard {
+    fn new(state: OwnedMutexGuard<State>) -> crate::Result<Self> {
+        if state.tx.is_none() {
+            return Err(crate::Error::AlreadyConsumed);
+        }
+        Ok(Self { state })
+    }
+
+    /// Decides the outcome regardless of the response: commit if `true`, roll back if
+    /// `false`.
+    pub fn set_commit(&mut self, commit: bool) {
+        self.state.commit = Some(commit);
+    }
+
+    /// Runs `work` after the transaction has been committed.
+    ///
+    /// The work is dropped without running if the transaction is rolled back or the
+    /// commit fails, which makes it the place for side effects that must only happen
+    /// once the data is durable, such as publishing events or sending emails.
+    pub fn after_commit<F>(&mut self, work: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.state.after_commit.push(Box::pin(work));
+    }
+}
+
+impl Deref for RequestTransactionGuard {
+    type Target = TransactionContext<'static>;
+
+    fn deref(&self) -> &Self::Target {
+        self.state
+            .tx
+            .as_ref()
+            .expect("request transaction checked when locked")
+    }
+}
+
+impl DerefMut for RequestTransactionGuard {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.state
+            .tx
+            .as_mut()
+            .expect("request transaction checked when locked")
+    }
+}
+
+impl fmt::Debug for RequestTransactionGuard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RequestTransactionGuard")
+            .field("tx", &self.state.tx)
+            .field("commit", &self.state.commit)
+            .field("after_commit", &self.state.after_commit.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_finished_transaction_cannot_be_locked() {
+        let tx = RequestTransaction {
+            inner: Arc::new(Mutex::new(State {
+                tx: None,
+                commit: None,
+                after_commit: Vec::new(),
+            })),
+        };
+        assert_eq!(tx.lock().await.unwrap_err().kind(), "already_consumed");
+        assert!(tx.try_lock().unwrap().is_err());
+        tx.finish(true).await.unwrap();
+    }
+}
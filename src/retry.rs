@@ -0,0 +1,121 @@
+use crate::error::{ER_LOCK_DEADLOCK, ER_LOCK_WAIT_TIMEOUT};
+
+/// Policy for re-running a transaction that failed with a transient lock error.
+///
+/// A transaction is retried when it fails with a deadlock (MySQL error 1213) or a
+/// lock wait timeout (error 1205), up to `max_attempts` runs in total. Retries start
+/// immediately; the rolled-back transaction has already released its locks.
+///
+/// The default policy runs the transaction once and never retries.
+///
+/// # Examples
+///
+/// ```rust
+/// use sqlx_transaction_manager::RetryPolicy;
+///
+/// let policy = RetryPolicy::attempts(3);
+/// assert_eq!(policy.max_attempts(), 3);
+/// assert_eq!(RetryPolicy::default().max_attempts(), 1);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+}
+
+impl RetryPolicy {
+    /// Runs the transaction once, without retrying.
+    pub fn none() -> Self {
+        Self { max_attempts: 1 }
+    }
+
+    /// Runs the transaction up to `max_attempts` times in total.
+    ///
+    /// A value of `0` is treated as `1`.
+    pub fn attempts(max_attempts: u32) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+        }
+    }
+
+    /// Returns the maximum number of runs, including the first one.
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// Returns `true` if a run that failed with `error` on attempt `attempt`
+    /// (starting at 1) should be retried.
+    pub fn should_retry(&self, attempt: u32, error: &crate::Error) -> bool {
+        self.should_retry_error(attempt, error)
+    }
+
+    /// Like [`should_retry`](Self::should_retry), for any error type that can expose the
+    /// MySQL error number, such as application errors wrapping [`crate::Error`].
+    pub fn should_retry_error<E: RetryableError + ?Sized>(&self, attempt: u32, error: &E) -> bool {
+        attempt < self.max_attempts
+            && matches!(
+                error.mysql_error_number(),
+                Some(ER_LOCK_DEADLOCK | ER_LOCK_WAIT_TIMEOUT)
+            )
+    }
+}
+
+/// Errors a [`RetryPolicy`] can inspect for transient lock failures.
+///
+/// Implement this for application error types that wrap [`crate::Error`], so they can
+/// be retried by [`RetryPolicy::should_retry_error`] and the `#[transactional]` macro.
+pub trait RetryableError {
+    /// Returns the MySQL server error number, if this is a server-reported error.
+    fn mysql_error_number(&self) -> Option<u16>;
+}
+
+impl RetryableError for crate::Error {
+    fn mysql_error_number(&self) -> Option<u16> {
+        crate::Error::mysql_error_number(self)
+    }
+}
+
+impl RetryableError for sqlx::Error {
+    fn mysql_error_number(&self) -> Option<u16> {
+        match self {
+            sqlx::Error::Database(e) => e
+                .try_downcast_ref::<sqlx::mysql::MySqlDatabaseError>()
+                .map(|e| e.number()),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "anyhow")]
+impl RetryableError for anyhow::Error {
+    fn mysql_error_number(&self) -> Option<u16> {
+        if let Some(error) = self.downcast_ref::<crate::Error>() {
+            return error.mysql_error_number();
+        }
+        self.downcast_ref::<sqlx::Error>()
+            .and_then(RetryableError::mysql_error_number)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zero_attempts_still_runs_once() {
+        assert_eq!(RetryPolicy::attempts(0).max_attempts(), 1);
+    }
+
+    #[test]
+    fn test_non_lock_errors_are_not_retried() {
+        let policy = RetryPolicy::attempts(3);
+        let error = crate::Error::Database(sqlx::Error::RowNotFound);
+        assert!(!policy.should_retry(1, &error));
+        assert!(!policy.should_retry(1, &crate::Error::AlreadyConsumed));
+    }
+}
@@ -0,0 +1,252 @@
+//! A transaction shared between several owners.
+//!
+//! Requires the `shared` feature.
+
+use crate::context::TransactionContext;
+use crate::error::ErrorContext;
+use std::borrow::Cow;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::{Mutex, MutexGuard};
+
+/// Cloneable handle to a transaction, for repositories and tasks that each hold "the
+/// current transaction".
+///
+/// Statements are serialized: an owner [`lock`](Self::lock)s the transaction to run
+/// queries on it, and other owners wait until the guard is dropped.
+///
+/// The transaction is finished either explicitly, by any owner calling
+/// [`commit`](Self::commit) or [`rollback`](Self::rollback), or when the last owner calls
+/// [`finish`](Self::finish). After that, every handle fails with
+/// [`Error::AlreadyConsumed`](crate::Error::AlreadyConsumed). If every handle is dropped
+/// without finishing, the transaction is rolled back like a dropped
+/// [`TransactionContext`].
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use sqlx::MySqlPool;
+/// use sqlx_transaction_manager::{SharedTransaction, TransactionContext};
+///
+/// struct UserRepository {
+///     tx: SharedTransaction,
+/// }
+///
+/// impl UserRepository {
+///     async fn insert(&self, name: &str) -> sqlx_transaction_manager::Result<()> {
+///         let mut tx = self.tx.lock().await?;
+///         sqlx::query("INSERT INTO users (name) VALUES (?)")
+///             .bind(name)
+///             .execute(&mut *tx)
+///             .await?;
+///         Ok(())
+///     }
+/// }
+///
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// # let pool = MySqlPool::connect("mysql://localhost/test").await?;
+/// let tx = SharedTransaction::new(TransactionContext::begin(&pool).await?);
+/// let users = UserRepository { tx: tx.clone() };
+///
+/// users.insert("Alice").await?;
+/// drop(users);
+/// tx.finish().await?; // The last owner commits
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct SharedTransaction {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    tx: Mutex<Option<TransactionContext<'static>>>,
+    rollback_only: AtomicBool,
+    label: Option<Cow<'static, str>>,
+}
+
+impl SharedTransaction {
+    /// Shares `tx` between the owners of the returned handle and its clones.
+    pub fn new(tx: TransactionContext<'static>) -> Self {
+        let label = tx.label().map(|label| Cow::Owned(label.to_owned()));
+        Self {
+            inner: Arc::new(Inner {
+                tx: Mutex::new(Some(tx)),
+                rollback_only: AtomicBool::new(false),
+                label,
+            }),
+        }
+    }
+
+    /// Returns the transaction's label, if any.
+    pub fn label(&self) -> Option<&str> {
+        self.inner.label.as_deref()
+    }
+
+    /// Locks the transaction for running queries, waiting for other owners to release it.
+    ///
+    /// Returns [`Error::AlreadyConsumed`](crate::Error::AlreadyConsumed) if the transaction
+    /// has been committed or rolled back.
+    pub async fn lock(&self) -> crate::Result<SharedTransactionGuard<'_>> {
+        let guard = self.inner.tx.lock().await;
+        if guard.is_none() {
+            return Err(self.consumed());
+        }
+        Ok(SharedTransactionGuard { guard })
+    }
+
+    /// Marks the transaction so that [`finish`](Self::finish) rolls it back instead of
+    /// committing.
+    ///
+    /// Use it when one owner fails but cannot decide for the others.
+    pub fn set_rollback_only(&self) {
+        self.inner.rollback_only.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns `true` if [`set_rollback_only`](Self::set_rollback_only) has been called.
+    pub fn is_rollback_only(&self) -> bool {
+        self.inner.rollback_only.load(Ordering::Relaxed)
+    }
+
+    /// Commits the transaction now, on behalf of every owner.
+    ///
+    /// Returns [`Error::AlreadyConsumed`](crate::Error::AlreadyConsumed) if the transaction
+    /// has already been committed or rolled back.
+    pub async fn commit(&self) -> crate::Result<()> {
+        self.take().await?.commit().await
+    }
+
+    /// Rolls the transaction back now, on behalf of every owner.
+    ///
+    /// Returns [`Error::AlreadyConsumed`](crate::Error::AlreadyConsumed) if the transaction
+    /// has already been committed or rolled back.
+    pub async fn rollback(&self) -> crate::Result<()> {
+        self.take().await?.rollback().await
+    }
+
+    /// Gives up this handle, finishing the transaction if it is the last one.
+    ///
+    /// The last owner commits the transaction, or rolls it back if it has been marked
+    /// with [`set_rollback_only`](Self::set_rollback_only). Other owners only drop their
+    /// handle. Finishing a transaction that was already committed or rolled back
+    /// explicitly does nothing.
+    pub async fn finish(self) -> crate::Result<()> {
+        let Some(inner) = Arc::into_inner(self.inner) else {
+            return Ok(());
+        };
+        match inner.tx.into_inner() {
+            Some(tx) if inner.rollback_only.into_inner() => tx.rollback().await,
+            Some(tx) => tx.commit().await,
+            None => Ok(()),
+        }
+    }
+
+    /// Takes the transaction out, leaving every handle consumed.
+    pub(crate) async fn take(&self) -> crate::Result<TransactionContext<'static>> {
+        self.inner
+            .tx
+            .lock()
+            .await
+            .take()
+            .ok_or_else(|| self.consumed())
+    }
+
+    fn consumed(&self) -> crate::Error {
+        crate::Error::AlreadyConsumed.with_context(ErrorContext {
+            label: self.inner.label.clone(),
+            ..ErrorContext::default()
+        })
+    }
+}
+
+impl From<TransactionContext<'static>> for SharedTransaction {
+    fn from(tx: TransactionContext<'static>) -> Self {
+        Self::new(tx)
+    }
+}
+
+impl fmt::Debug for SharedTransaction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedTransaction")
+            .field("label", &self.inner.label)
+            .field("owners", &Arc::strong_count(&self.inner))
+            .field("rollback_only", &self.is_rollback_only())
+            .finish()
+    }
+}
+
+/// Exclusive access to a [`SharedTransaction`], returned by [`SharedTransaction::lock`].
+///
+/// Dereferences to the [`TransactionContext`], so `&mut *guard` can be passed to sqlx
+/// queries.
+pub struct SharedTransactionGuard<'a> {
+    guard: MutexGuard<'a, Option<TransactionContext<'static>>>,
+}
+
+impl Deref for SharedTransactionGuard<'_> {
+    type Target = TransactionContext<'static>;
+
+    fn deref(&self) -> &Self::Target {
+        self.guard
+            .as_ref()
+            .expect("shared transaction checked when locked")
+    }
+}
+
+impl DerefMut for SharedTransactionGuard<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.guard
+            .as_mut()
+            .expect("shared transaction checked when locked")
+    }
+}
+
+impl fmt::Debug for SharedTransactionGuard<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.deref().fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn consumed(label: &'static str) -> SharedTransaction {
+        SharedTransaction {
+            inner: Arc::new(Inner {
+                tx: Mutex::new(None),
+                rollback_only: AtomicBool::new(false),
+                label: Some(label.into()),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_handles_are_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync + 'static>() {}
+        assert_send_sync::<SharedTransaction>();
+    }
+
+    #[tokio::test]
+    async fn test_consumed_transaction_reports_already_consumed() {
+        let tx = consumed("checkout.place_order");
+        let error = tx.lock().await.unwrap_err();
+        assert_eq!(error.kind(), "already_consumed");
+        assert_eq!(error.label(), Some("checkout.place_order"));
+        assert_eq!(tx.commit().await.unwrap_err().kind(), "already_consumed");
+        assert_eq!(tx.rollback().await.unwrap_err().kind(), "already_consumed");
+    }
+
+    #[tokio::test]
+    async fn test_rollback_only_is_shared_between_clones() {
+        let tx = consumed("checkout");
+        let other = tx.clone();
+        other.set_rollback_only();
+        assert!(tx.is_rollback_only());
+
+        other.finish().await.unwrap();
+        tx.finish().await.unwrap();
+    }
+}
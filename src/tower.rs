@@ -0,0 +1,321 @@
+//! A [tower](https://docs.rs/tower) layer that runs each request in a transaction.
+//!
+//! Requires the `tower` feature. [`TransactionLayer`] wraps an HTTP service, such as a
+//! tonic gRPC server or a hyper service, with the semantics of
+//! [`with_transaction`](crate::with_transaction): a transaction is begun for every
+//! request, handed to the service as a [`RequestTransaction`] in the request's
+//! extensions, and committed or rolled back once the service responds.
+//!
+//! Whether to commit is decided by a [`CommitPredicate`] looking at the service's
+//! result. The default, [`SuccessStatus`], commits 2xx responses. A handler can override
+//! the decision with [`RequestTransactionGuard::set_commit`](crate::request::RequestTransactionGuard::set_commit).
+//!
+//! Failures to begin or commit are handled by an [`OnError`]. The default, [`ReturnError`],
+//! returns them as the service's error. Services that cannot fail, such as tonic servers
+//! and `Routes`, whose error type is `Infallible`, need
+//! [`on_error`](TransactionLayer::on_error) to turn them into a response instead.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use sqlx::MySqlPool;
+//! use sqlx_transaction_manager::tower::TransactionLayer;
+//! use sqlx_transaction_manager::TransactionOptions;
+//!
+//! type BoxError = Box<dyn std::error::Error + Send + Sync>;
+//!
+//! # fn example<B>(pool: MySqlPool) {
+//! let layer = TransactionLayer::new(pool)
+//!     .options(TransactionOptions::new().label("grpc"))
+//!     // gRPC reports failures through the `grpc-status` header rather than the HTTP status
+//!     .commit_when(|result: &Result<http::Response<B>, BoxError>| match result {
+//!         Ok(response) => response.headers().get("grpc-status").is_none_or(|s| s == "0"),
+//!         Err(_) => false,
+//!     });
+//! # }
+//! ```
+//!
+//! For an `Infallible` service, respond to begin and commit failures instead. With tonic,
+//! `tonic::Status::unavailable(error.to_string()).into_http()` builds the response:
+//!
+//! ```rust,no_run
+//! use sqlx::MySqlPool;
+//! use sqlx_transaction_manager::tower::TransactionLayer;
+//!
+//! # fn example(pool: MySqlPool) {
+//! let layer = TransactionLayer::new(pool).on_error(|_error: sqlx_transaction_manager::Error| {
+//!     http::Response::builder()
+//!         .status(http::StatusCode::SERVICE_UNAVAILABLE)
+//!         .body(String::new())
+//!         .unwrap()
+//! });
+//! # }
+//! ```
+//!
+//! Inside the service, take the transaction from the request's extensions:
+//!
+//! ```rust,no_run
+//! use sqlx_transaction_manager::tower::RequestTransaction;
+//!
+//! async fn handle(request: http::Request<String>) -> sqlx_transaction_manager::Result<()> {
+//!     let tx = request.extensions().get::<RequestTransaction>().unwrap();
+//!     let mut tx = tx.lock().await?;
+//!     sqlx::query("INSERT INTO audit_log (action) VALUES (?)")
+//!         .bind(request.body())
+//!         .execute(&mut *tx)
+//!         .await?;
+//!     Ok(())
+//! }
+//! ```
+
+use crate::context::TransactionContext;
+use crate::options::TransactionOptions;
+use futures_core::future::BoxFuture;
+use sqlx::MySqlPool;
+use std::task::{Context, Poll};
+use tower_layer::Layer;
+use tower_service::Service;
+
+pub use crate::request::{RequestTransaction, RequestTransactionGuard};
+
+/// Decides whether a request's transaction is committed, from the inner service's result.
+///
+/// Implemented for closures taking `&Result<Response, Error>` and returning `bool`.
+pub trait CommitPredicate<Res, E> {
+    /// Returns `true` to commit the transaction, `false` to roll it back.
+    fn should_commit(&self, result: &Result<Res, E>) -> bool;
+}
+
+impl<F, Res, E> CommitPredicate<Res, E> for F
+where
+    F: Fn(&Result<Res, E>) -> bool,
+{
+    fn should_commit(&self, result: &Result<Res, E>) -> bool {
+        self(result)
+    }
+}
+
+/// The default [`CommitPredicate`]: commits when the service returns a 2xx response.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SuccessStatus;
+
+impl<B, E> CommitPredicate<http::Response<B>, E> for SuccessStatus {
+    fn should_commit(&self, result: &Result<http::Response<B>, E>) -> bool {
+        matches!(result, Ok(response) if response.status().is_success())
+    }
+}
+
+/// Handles a failure to begin or commit a request's transaction.
+///
+/// Implemented for closures taking the [`crate::Error`] and returning the service's
+/// response, which is sent instead of the service's own.
+pub trait OnError<Res, E> {
+    /// Returns the result to hand back to the caller of the service.
+    fn on_error(&self, error: crate::Error) -> Result<Res, E>;
+}
+
+impl<F, Res, E> OnError<Res, E> for F
+where
+    F: Fn(crate::Error) -> Res,
+{
+    fn on_error(&self, error: crate::Error) -> Result<Res, E> {
+        Ok(self(error))
+    }
+}
+
+/// The default [`OnError`]: returns the failure as the service's error, which must
+/// implement `From<sqlx_transaction_manager::Error>`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ReturnError;
+
+impl<Res, E: From<crate::Error>> OnError<Res, E> for ReturnError {
+    fn on_error(&self, error: crate::Error) -> Result<Res, E> {
+        Err(error.into())
+    }
+}
+
+/// Layer that runs every request to the wrapped service in a transaction.
+///
+/// By default the wrapped service's error type must implement
+/// `From<sqlx_transaction_manager::Error>`, so failures to begin or commit the transaction
+/// can be returned; `tower::BoxError` does. For services whose error type is
+/// `Infallible`, such as tonic servers, respond to failures with
+/// [`on_error`](Self::on_error). For Axum routers, `axum::transaction_middleware` is
+/// simpler.
+#[derive(Debug, Clone)]
+pub struct TransactionLayer<P = SuccessStatus, H = ReturnError> {
+    pool: MySqlPool,
+    options: TransactionOptions,
+    predicate: P,
+    on_error: H,
+}
+
+impl TransactionLayer {
+    /// Creates a layer beginning transactions on `pool` with default options, which
+    /// commits 2xx responses and returns failures as the service's error.
+    pub fn new(pool: MySqlPool) -> Self {
+        Self {
+            pool,
+            options: TransactionOptions::default(),
+            predicate: SuccessStatus,
+            on_error: ReturnError,
+        }
+    }
+}
+
+impl<P, H> TransactionLayer<P, H> {
+    /// Sets the options every transaction is begun with.
+    pub fn options(mut self, options: TransactionOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Sets the predicate that decides whether to commit.
+    pub fn commit_when<Q>(self, predicate: Q) -> TransactionLayer<Q, H> {
+        TransactionLayer {
+            pool: self.pool,
+            options: self.options,
+            predicate,
+            on_error: self.on_error,
+        }
+    }
+
+    /// Sets how failures to begin or commit the transaction are handed back.
+    ///
+    /// Pass a closure turning the [`crate::Error`] into the service's response. If the
+    /// commit fails, that response replaces the service's own.
+    pub fn on_error<G>(self, on_error: G) -> TransactionLayer<P, G> {
+        TransactionLayer {
+            pool: self.pool,
+            options: self.options,
+            predicate: self.predicate,
+            on_error,
+        }
+    }
+}
+
+impl<S, P: Clone, H: Clone> Layer<S> for TransactionLayer<P, H> {
+    type Service = TransactionService<S, P, H>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TransactionService {
+            inner,
+            pool: self.pool.clone(),
+            options: self.options.clone(),
+            predicate: self.predicate.clone(),
+            on_error: self.on_error.clone(),
+        }
+    }
+}
+
+/// Service created by [`TransactionLayer`].
+#[derive(Debug, Clone)]
+pub struct TransactionService<S, P = SuccessStatus, H = ReturnError> {
+    inner: S,
+    pool: MySqlPool,
+    options: TransactionOptions,
+    predicate: P,
+    on_error: H,
+}
+
+impl<S, P, H, ReqBody> Service<http::Request<ReqBody>> for TransactionService<S, P, H>
+where
+    S: Service<http::Request<ReqBody>> + Clone + Send + 'static,
+    S::Future: Send,
+    S::Response: Send,
+    S::Error: Send,
+    P: CommitPredicate<S::Response, S::Error> + Clone + Send + Sync + 'static,
+    H: OnError<S::Response, S::Error> + Clone + Send + Sync + 'static,
+    ReqBody: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<S::Response, S::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: http::Request<ReqBody>) -> Self::Future {
+        // Use the service that was polled ready, leaving a fresh clone in its place.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let pool = self.pool.clone();
+        let options = self.options.clone();
+        let predicate = self.predicate.clone();
+        let on_error = self.on_error.clone();
+
+        Box::pin(async move {
+            let tx = match TransactionContext::begin_with(&pool, options).await {
+                Ok(tx) => RequestTransaction::new(tx),
+                Err(e) => return on_error.on_error(e),
+            };
+            request.extensions_mut().insert(tx.clone());
+
+            let result = inner.call(request).await;
+            match tx.finish(predicate.should_commit(&result)).await {
+                Ok(()) => result,
+                Err(e) => on_error.on_error(e),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::Infallible;
+
+    fn response(status: u16) -> Result<http::Response<()>, Infallible> {
+        Ok(http::Response::builder().status(status).body(()).unwrap())
+    }
+
+    #[test]
+    fn test_success_status_commits_2xx() {
+        assert!(SuccessStatus.should_commit(&response(200)));
+        assert!(SuccessStatus.should_commit(&response(204)));
+        assert!(!SuccessStatus.should_commit(&response(409)));
+        assert!(!SuccessStatus.should_commit(&Err::<http::Response<()>, _>(())));
+    }
+
+    #[test]
+    fn test_closures_are_predicates() {
+        let predicate = |result: &Result<http::Response<()>, Infallible>| result.is_ok();
+        assert!(predicate.should_commit(&response(500)));
+    }
+
+    fn unreachable_pool() -> MySqlPool {
+        sqlx::mysql::MySqlPoolOptions::new()
+            .acquire_timeout(std::time::Duration::from_millis(50))
+            .connect_lazy("mysql://localhost:1/test")
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_begin_failure_is_returned_as_error() {
+        let mut service = TransactionLayer::new(unreachable_pool()).layer(::tower::service_fn(
+            |_request: http::Request<()>| async move {
+                Ok::<_, ::tower::BoxError>(http::Response::new(()))
+            },
+        ));
+
+        let result = service.call(http::Request::new(())).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_infallible_services_respond_to_begin_failure() {
+        let layer = TransactionLayer::new(unreachable_pool()).on_error(|_error: crate::Error| {
+            http::Response::builder().status(503).body(()).unwrap()
+        });
+        let mut service =
+            layer.layer(::tower::service_fn(
+                |_request: http::Request<()>| async move {
+                    Ok::<_, Infallible>(http::Response::new(()))
+                },
+            ));
+
+        let response = service.call(http::Request::new(())).await.unwrap();
+        assert_eq!(response.status(), 503);
+    }
+}
@@ -0,0 +1,188 @@
+use sqlx::{MySqlConnection, MySqlPool};
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Marker-row configuration for commit-outcome verification.
+///
+/// When enabled, a unique marker row is inserted into `table` as the first statement
+/// of the transaction. If `COMMIT` then fails in a way that leaves its outcome unknown
+/// (for example the connection drops), the marker is looked up on a fresh pool
+/// connection to decide whether the transaction was applied.
+///
+/// The table must exist and have a string column named `marker`:
+///
+/// ```sql
+/// CREATE TABLE tx_commit_markers (
+///     marker VARCHAR(64) NOT NULL PRIMARY KEY,
+///     created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+/// );
+/// ```
+///
+/// Marker rows are never deleted by this crate; purge old rows periodically.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use sqlx::MySqlPool;
+/// use sqlx_transaction_manager::{CommitMarker, TransactionContext, TransactionOptions};
+///
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// # let pool = MySqlPool::connect("mysql://localhost/test").await?;
+/// let options = TransactionOptions::new()
+///     .verify_commit(CommitMarker::new("tx_commit_markers"));
+/// let mut tx = TransactionContext::begin_with(&pool, options).await?;
+/// // ... perform operations
+/// tx.commit().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitMarker {
+    table: String,
+}
+
+impl CommitMarker {
+    /// Creates a marker configuration writing to `table`.
+    ///
+    /// The table name is interpolated into SQL as-is, so it must be a trusted identifier.
+    pub fn new(table: impl Into<String>) -> Self {
+        Self {
+            table: table.into(),
+        }
+    }
+
+    /// Returns the name of the marker table.
+    pub fn table(&self) -> &str {
+        &self.table
+    }
+
+    /// Inserts a fresh marker row through the transaction's connection.
+    pub(crate) async fn write(
+        &self,
+        pool: &MySqlPool,
+        conn: &mut MySqlConnection,
+    ) -> crate::Result<PendingCommitCheck> {
+        let id = new_marker_id();
+        sqlx::query(&format!("INSERT INTO {} (marker) VALUES (?)", self.table))
+            .bind(&id)
+            .execute(conn)
+            .await?;
+
+        Ok(PendingCommitCheck {
+            pool: pool.clone(),
+            table: self.table.clone(),
+            id,
+        })
+    }
+}
+
+/// Outcome of a commit whose acknowledgement was lost.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommitOutcome {
+    /// The marker row is visible, so the transaction was applied.
+    Committed,
+    /// The marker row is absent, so the transaction was not applied and can be retried.
+    NotCommitted,
+    /// The marker could not be checked; the transaction may or may not have been applied.
+    Unknown,
+}
+
+impl fmt::Display for CommitOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommitOutcome::Committed => f.write_str("committed"),
+            CommitOutcome::NotCommitted => f.write_str("not committed"),
+            CommitOutcome::Unknown => f.write_str("outcome unknown"),
+        }
+    }
+}
+
+/// A marker written at begin time, waiting to be checked if the commit is ambiguous.
+#[derive(Debug)]
+pub(crate) struct PendingCommitCheck {
+    pool: MySqlPool,
+    table: String,
+    id: String,
+}
+
+impl PendingCommitCheck {
+    /// Returns the marker value written for this transaction.
+    pub(crate) fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Looks the marker up on a fresh pool connection.
+    ///
+    /// The lookup is a locking read, so it waits for the original transaction to
+    /// finish if the server is still processing it. A failed lookup (including a
+    /// lock wait timeout) reports [`CommitOutcome::Unknown`].
+    pub(crate) async fn check(&self) -> CommitOutcome {
+        let sql = format!(
+            "SELECT COUNT(*) FROM {} WHERE marker = ? LOCK IN SHARE MODE",
+            self.table
+        );
+        let found: Result<(i64,), sqlx::Error> = sqlx::query_as(&sql)
+            .bind(&self.id)
+            .fetch_one(&self.pool)
+            .await;
+
+        match found {
+            Ok((0,)) => CommitOutcome::NotCommitted,
+            Ok(_) => CommitOutcome::Committed,
+            Err(_) => CommitOutcome::Unknown,
+        }
+    }
+}
+
+/// Returns `true` if a failed `COMMIT` may still have been applied by the server.
+///
+/// Errors reported by the server itself mean the commit was rejected; transport-level
+/// failures mean the acknowledgement was lost and the outcome is unknown.
+pub(crate) fn is_ambiguous(error: &sqlx::Error) -> bool {
+    matches!(
+        error,
+        sqlx::Error::Io(_) | sqlx::Error::Protocol(_) | sqlx::Error::WorkerCrashed
+    )
+}
+
+/// Generates a marker value unique across processes and hosts with high probability.
+fn new_marker_id() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or_default();
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+    hasher.write_u32(std::process::id());
+    hasher.write_u64(nanos);
+
+    format!("{:016x}{:016x}", nanos, hasher.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use std::io;
+
+    #[test]
+    fn test_marker_ids_are_unique() {
+        let ids: HashSet<_> = (0..1000).map(|_| new_marker_id()).collect();
+        assert_eq!(ids.len(), 1000);
+        assert!(ids.iter().all(|id| id.len() == 32));
+    }
+
+    #[test]
+    fn test_transport_errors_are_ambiguous() {
+        let io_error = sqlx::Error::Io(io::Error::from(io::ErrorKind::ConnectionReset));
+        assert!(is_ambiguous(&io_error));
+        assert!(is_ambiguous(&sqlx::Error::WorkerCrashed));
+        assert!(!is_ambiguous(&sqlx::Error::RowNotFound));
+        assert!(!is_ambiguous(&sqlx::Error::PoolTimedOut));
+    }
+}